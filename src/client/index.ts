@@ -6,16 +6,46 @@ import {
   Transaction,
   sendAndConfirmTransaction,
   SYSVAR_RENT_PUBKEY,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+  Ed25519Program,
 } from '@solana/web3.js';
 import * as anchor from '@project-serum/anchor';
 import { Program, AnchorProvider, web3, BN } from '@project-serum/anchor';
-import { TOKEN_PROGRAM_ID, getAssociatedTokenAddress } from '@solana/spl-token';
+import { TOKEN_PROGRAM_ID, createApproveInstruction } from '@solana/spl-token';
 import { Buffer } from 'buffer';
 
-// Define the TokenType enum to match our Rust contract
-export enum TokenType {
-  SOL = 0,
-  USDC = 1,
+// Owner of the ProgramData account that records the program's upgrade authority
+const BPF_LOADER_UPGRADEABLE_PROGRAM_ID = new PublicKey('BPFLoaderUpgradeab1e11111111111111111111111');
+
+// Settings accepted by initialize_config / update_config
+export interface ConfigParams {
+  treasury: PublicKey;
+  protocolFeeBps: number;
+  oraclePrograms: PublicKey[];
+  magicBlockAuthority: PublicKey;
+  guardian: PublicKey;
+  defaultFeeBps: number;
+  restrictPoolCreation: boolean;
+}
+
+// Operations that can be paused, matching the PAUSE_* bits of the Rust contract
+export enum PauseFlags {
+  Swap = 1 << 0,
+  Deposit = 1 << 1,
+  Withdraw = 1 << 2,
+  Delegation = 1 << 3,
+  All = Swap | Deposit | Withdraw | Delegation,
+}
+
+// Define the SwapDirection enum to match our Rust contract
+export enum SwapDirection {
+  BaseToQuote = 0,
+  QuoteToBase = 1,
+}
+
+export enum CurveType {
+  ConstantProduct = 0,
+  OracleAnchored = 1,
 }
 
 export class BulkerDexClient {
@@ -46,33 +76,170 @@ export class BulkerDexClient {
     this.program = new Program(idl, programId, this.provider);
   }
 
+  /**
+   * Initialize the global config (once per deployment, by the program's upgrade authority)
+   */
+  async initializeConfig(params: ConfigParams): Promise<PublicKey> {
+    const [configPda, _] = await this.findConfigAddress();
+    const [programData, __] = PublicKey.findProgramAddressSync(
+      [this.programId.toBuffer()],
+      BPF_LOADER_UPGRADEABLE_PROGRAM_ID
+    );
+    
+    await this.program.methods
+      .initializeConfig(params)
+      .accounts({
+        config: configPda,
+        admin: this.wallet.publicKey,
+        programData: programData,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    
+    return configPda;
+  }
+
+  /**
+   * Replace the global config settings (config admin only)
+   */
+  async updateConfig(params: ConfigParams): Promise<string> {
+    const [configPda, _] = await this.findConfigAddress();
+    
+    const txId = await this.program.methods
+      .updateConfig(params)
+      .accounts({
+        config: configPda,
+        admin: this.wallet.publicKey,
+      })
+      .rpc();
+    
+    return txId;
+  }
+
+  /**
+   * Set the Pyth Lazer signing keys swapLazer accepts; empty disables Lazer pricing (admin only)
+   */
+  async setTrustedSigners(trustedSigners: PublicKey[]): Promise<string> {
+    const [configPda, _] = await this.findConfigAddress();
+    
+    const txId = await this.program.methods
+      .setTrustedSigners(trustedSigners)
+      .accounts({
+        config: configPda,
+        admin: this.wallet.publicKey,
+      })
+      .rpc();
+    
+    return txId;
+  }
+
+  /**
+   * Propose a new config admin, who must then call acceptAdmin
+   */
+  async transferAdmin(newAdmin: PublicKey): Promise<string> {
+    const [configPda, _] = await this.findConfigAddress();
+    
+    const txId = await this.program.methods
+      .transferAdmin(newAdmin)
+      .accounts({
+        config: configPda,
+        admin: this.wallet.publicKey,
+      })
+      .rpc();
+    
+    return txId;
+  }
+
+  /**
+   * Accept a pending admin transfer with the wallet as the new admin
+   */
+  async acceptAdmin(): Promise<string> {
+    const [configPda, _] = await this.findConfigAddress();
+    
+    const txId = await this.program.methods
+      .acceptAdmin()
+      .accounts({
+        config: configPda,
+        newAdmin: this.wallet.publicKey,
+      })
+      .rpc();
+    
+    return txId;
+  }
+
+  /**
+   * Fetch the global config
+   */
+  async getConfig(): Promise<any> {
+    const [configPda, _] = await this.findConfigAddress();
+    const config = await this.program.account.config.fetch(configPda);
+    return {
+      admin: config.admin.toString(),
+      pendingAdmin: config.pendingAdmin.toString(),
+      treasury: config.treasury.toString(),
+      protocolFeeBps: config.protocolFeeBps,
+      oraclePrograms: config.oraclePrograms.map((p: PublicKey) => p.toString()),
+      magicBlockAuthority: config.magicBlockAuthority.toString(),
+      defaultFeeBps: config.defaultFeeBps,
+      restrictPoolCreation: config.restrictPoolCreation,
+      guardian: config.guardian.toString(),
+      paused: config.paused,
+    };
+  }
+
   /**
    * Initialize a new pool
    */
   async initializePool(
-    initialSol: number,
-    initialUsdc: number,
+    baseMint: PublicKey,
+    quoteMint: PublicKey,
+    oracle: PublicKey,
     concentrationFactor: number,
-    magicBlockAuth: PublicKey
+    maxConfidenceBps: number,
+    confidenceMultiplierBps: number,
+    maxOracleAgeSlots: number,
+    maxOracleAgeSecs: number,
+    feeBps: number | null,
+    targetBaseBps: number = 5000,
+    rebalanceCapBps: number = 100,
+    curveType: CurveType = CurveType.ConstantProduct,
+    baseSpreadBps: number = 0, // Oracle-anchored curves only
+    minFeeBps: number = 0,
+    maxFeeBps: number = 0 // 0 keeps the fee flat at feeBps
   ): Promise<PublicKey> {
-    // Find the pool PDA
-    const [poolPda, _] = await this.findPoolAddress(this.wallet.publicKey);
-    
-    // Convert to BN for the contract
-    const initialSolBN = new BN(initialSol);
-    const initialUsdcBN = new BN(initialUsdc);
+    // Find the pool and LP mint PDAs
+    const [poolPda, _] = await this.findPoolAddress(this.wallet.publicKey, baseMint, quoteMint);
+    const [lpMint, __] = await this.findLpMintAddress(poolPda);
+    const { baseVault, quoteVault } = await this.findVaultAddresses(poolPda, baseMint, quoteMint);
+    const [configPda, ___] = await this.findConfigAddress();
     
     // Call the initialize_pool instruction
     await this.program.methods
-      .initializePool(
-        initialSolBN,
-        initialUsdcBN,
-        concentrationFactor
-      )
+      .initializePool({
+        concentrationFactor,
+        maxConfidenceBps,
+        confidenceMultiplierBps,
+        maxOracleAgeSlots: new BN(maxOracleAgeSlots),
+        maxOracleAgeSecs: new BN(maxOracleAgeSecs),
+        feeBps,
+        targetBaseBps,
+        rebalanceCapBps,
+        curveType: { [curveType === CurveType.OracleAnchored ? 'oracleAnchored' : 'constantProduct']: {} },
+        baseSpreadBps,
+        minFeeBps,
+        maxFeeBps,
+      })
       .accounts({
         pool: poolPda,
+        lpMint: lpMint,
+        baseMint: baseMint,
+        quoteMint: quoteMint,
+        baseVault: baseVault,
+        quoteVault: quoteVault,
+        oracle: oracle,
         authority: this.wallet.publicKey,
-        magicBlockAuth: magicBlockAuth,
+        config: configPda,
+        tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
       })
       .rpc();
@@ -81,21 +248,324 @@ export class BulkerDexClient {
   }
 
   /**
-   * Perform a swap via Magic Block delegation
+   * Update the swap fee of a pool (pool authority only)
+   */
+  async updateFee(
+    baseMint: PublicKey,
+    quoteMint: PublicKey,
+    feeBps: number
+  ): Promise<string> {
+    const [poolPda, _] = await this.findPoolAddress(this.wallet.publicKey, baseMint, quoteMint);
+    
+    const txId = await this.program.methods
+      .updateFee(feeBps)
+      .accounts({
+        pool: poolPda,
+        authority: this.wallet.publicKey,
+      })
+      .rpc();
+    
+    return txId;
+  }
+
+  /**
+   * Set the dynamic fee bounds of a pool; a max of 0 keeps the fee flat (pool authority only)
+   */
+  async setFeeBounds(
+    baseMint: PublicKey,
+    quoteMint: PublicKey,
+    minFeeBps: number,
+    maxFeeBps: number
+  ): Promise<string> {
+    const [poolPda, _] = await this.findPoolAddress(this.wallet.publicKey, baseMint, quoteMint);
+    
+    const txId = await this.program.methods
+      .setFeeBounds(minFeeBps, maxFeeBps)
+      .accounts({
+        pool: poolPda,
+        authority: this.wallet.publicKey,
+      })
+      .rpc();
+    
+    return txId;
+  }
+
+  /**
+   * Set the Pyth Lazer feed a pool prices swapLazer against; 0 disables it (pool authority only)
+   */
+  async setLazerFeed(
+    baseMint: PublicKey,
+    quoteMint: PublicKey,
+    feedId: number
+  ): Promise<string> {
+    const [poolPda, _] = await this.findPoolAddress(this.wallet.publicKey, baseMint, quoteMint);
+    
+    const txId = await this.program.methods
+      .setLazerFeed(feedId)
+      .accounts({
+        pool: poolPda,
+        authority: this.wallet.publicKey,
+      })
+      .rpc();
+    
+    return txId;
+  }
+
+  /**
+   * Set the target base value share and rebalance cap of a pool (pool authority only)
+   */
+  async setRebalanceParams(
+    baseMint: PublicKey,
+    quoteMint: PublicKey,
+    targetBaseBps: number,
+    rebalanceCapBps: number
+  ): Promise<string> {
+    const [poolPda, _] = await this.findPoolAddress(this.wallet.publicKey, baseMint, quoteMint);
+    
+    const txId = await this.program.methods
+      .setRebalanceParams(targetBaseBps, rebalanceCapBps)
+      .accounts({
+        pool: poolPda,
+        authority: this.wallet.publicKey,
+      })
+      .rpc();
+    
+    return txId;
+  }
+
+  /**
+   * Configure the oracle circuit breaker of a pool (pool authority only). Swaps and deposits
+   * fail with CircuitBreakerTripped on a trip; tripCircuitBreaker then pauses swaps until the
+   * guardian resumes them with setPoolPaused.
+   */
+  async setCircuitBreaker(
+    baseMint: PublicKey,
+    quoteMint: PublicKey,
+    thresholdBps: number,
+    windowSlots: number
+  ): Promise<string> {
+    const [poolPda, _] = await this.findPoolAddress(this.wallet.publicKey, baseMint, quoteMint);
+    
+    const txId = await this.program.methods
+      .setCircuitBreaker(thresholdBps, new BN(windowSlots))
+      .accounts({
+        pool: poolPda,
+        authority: this.wallet.publicKey,
+      })
+      .rpc();
+    
+    return txId;
+  }
+
+  /**
+   * Persist a circuit breaker trip by pausing the pool's swaps. Anyone may call this; it fails
+   * unless the oracle has moved past the breaker threshold within the window.
+   */
+  async tripCircuitBreaker(
+    poolAuthority: PublicKey,
+    baseMint: PublicKey,
+    quoteMint: PublicKey,
+    oraclePubkey: PublicKey
+  ): Promise<string> {
+    const [poolPda, _] = await this.findPoolAddress(poolAuthority, baseMint, quoteMint);
+    const [configPda, __] = await this.findConfigAddress();
+    
+    const txId = await this.program.methods
+      .tripCircuitBreaker()
+      .accounts({
+        pool: poolPda,
+        config: configPda,
+        oracle: oraclePubkey,
+      })
+      .rpc();
+    
+    return txId;
+  }
+
+  /**
+   * Set the paused operations of a pool, including clearing a tripped breaker (guardian only)
+   */
+  async setPoolPaused(
+    poolAuthority: PublicKey,
+    baseMint: PublicKey,
+    quoteMint: PublicKey,
+    paused: number
+  ): Promise<string> {
+    const [poolPda, _] = await this.findPoolAddress(poolAuthority, baseMint, quoteMint);
+    const [configPda, __] = await this.findConfigAddress();
+    
+    const txId = await this.program.methods
+      .setPoolPaused(paused)
+      .accounts({
+        config: configPda,
+        guardian: this.wallet.publicKey,
+        pool: poolPda,
+      })
+      .rpc();
+    
+    return txId;
+  }
+
+  /**
+   * Set the operations paused for every pool (guardian only)
+   */
+  async setGlobalPaused(paused: number): Promise<string> {
+    const [configPda, _] = await this.findConfigAddress();
+    
+    const txId = await this.program.methods
+      .setGlobalPaused(paused)
+      .accounts({
+        config: configPda,
+        guardian: this.wallet.publicKey,
+      })
+      .rpc();
+    
+    return txId;
+  }
+
+  /**
+   * Transfer accrued protocol fees of a pool to the treasury (config admin only)
+   */
+  async claimProtocolFees(
+    poolAuthority: PublicKey,
+    baseMint: PublicKey,
+    quoteMint: PublicKey,
+    treasuryBase: PublicKey,
+    treasuryQuote: PublicKey
+  ): Promise<string> {
+    const [poolPda, _] = await this.findPoolAddress(poolAuthority, baseMint, quoteMint);
+    const [configPda, __] = await this.findConfigAddress();
+    const { baseVault, quoteVault } = await this.findVaultAddresses(poolPda, baseMint, quoteMint);
+    
+    const txId = await this.program.methods
+      .claimProtocolFees()
+      .accounts({
+        config: configPda,
+        admin: this.wallet.publicKey,
+        pool: poolPda,
+        baseVault: baseVault,
+        quoteVault: quoteVault,
+        treasuryBase: treasuryBase,
+        treasuryQuote: treasuryQuote,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+    
+    return txId;
+  }
+
+  /**
+   * Deposit base and quote tokens into a pool in exchange for LP shares. Shares are priced at
+   * the current oracle price; the part beyond the pool's reserve ratio pays the swap fee.
+   */
+  async depositLiquidity(
+    poolAuthority: PublicKey,
+    baseMint: PublicKey,
+    quoteMint: PublicKey,
+    baseAmount: number,
+    quoteAmount: number,
+    minLpOut: number,
+    userBase: PublicKey,
+    userQuote: PublicKey,
+    userLp: PublicKey,
+    baseVault: PublicKey,
+    quoteVault: PublicKey,
+    oraclePubkey: PublicKey
+  ): Promise<string> {
+    // Find the pool and LP mint PDAs
+    const [poolPda, _] = await this.findPoolAddress(poolAuthority, baseMint, quoteMint);
+    const [lpMint, __] = await this.findLpMintAddress(poolPda);
+    const [configPda, ___] = await this.findConfigAddress();
+    
+    // Call the deposit_liquidity instruction
+    const txId = await this.program.methods
+      .depositLiquidity(
+        new BN(baseAmount),
+        new BN(quoteAmount),
+        new BN(minLpOut)
+      )
+      .accounts({
+        pool: poolPda,
+        config: configPda,
+        oracle: oraclePubkey,
+        lpMint: lpMint,
+        user: this.wallet.publicKey,
+        userBase: userBase,
+        userQuote: userQuote,
+        userLp: userLp,
+        baseVault: baseVault,
+        quoteVault: quoteVault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+    
+    return txId;
+  }
+
+  /**
+   * Burn LP shares for a pro-rata share of the pool vaults, less unclaimed protocol fees
+   */
+  async withdrawLiquidity(
+    poolAuthority: PublicKey,
+    baseMint: PublicKey,
+    quoteMint: PublicKey,
+    lpAmount: number,
+    minBaseOut: number,
+    minQuoteOut: number,
+    userBase: PublicKey,
+    userQuote: PublicKey,
+    userLp: PublicKey,
+    baseVault: PublicKey,
+    quoteVault: PublicKey
+  ): Promise<string> {
+    // Find the pool and LP mint PDAs
+    const [poolPda, _] = await this.findPoolAddress(poolAuthority, baseMint, quoteMint);
+    const [lpMint, __] = await this.findLpMintAddress(poolPda);
+    const [configPda, ___] = await this.findConfigAddress();
+    
+    // Call the withdraw_liquidity instruction
+    const txId = await this.program.methods
+      .withdrawLiquidity(
+        new BN(lpAmount),
+        new BN(minBaseOut),
+        new BN(minQuoteOut)
+      )
+      .accounts({
+        pool: poolPda,
+        config: configPda,
+        lpMint: lpMint,
+        user: this.wallet.publicKey,
+        userLp: userLp,
+        userBase: userBase,
+        userQuote: userQuote,
+        baseVault: baseVault,
+        quoteVault: quoteVault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+    
+    return txId;
+  }
+
+  /**
+   * Swap directly against the pool vaults
    */
   async swap(
     amountIn: number,
     minAmountOut: number,
-    inputToken: TokenType,
-    userSol: PublicKey,
-    userUsdc: PublicKey,
-    poolSol: PublicKey,
-    poolUsdc: PublicKey,
-    oraclePubkey: PublicKey
+    direction: SwapDirection,
+    baseMint: PublicKey,
+    quoteMint: PublicKey,
+    userBase: PublicKey,
+    userQuote: PublicKey,
+    baseVault: PublicKey,
+    quoteVault: PublicKey,
+    oraclePubkey: PublicKey,
+    maxPriceImpactBps: number = 0 // 0 disables the price impact check
   ): Promise<string> {
     // Find the pool PDA
-    const [poolPda, _] = await this.findPoolAddress(this.wallet.publicKey);
-    const [authority, __] = await this.findPoolAuthority(poolPda);
+    const [poolPda, _] = await this.findPoolAddress(this.wallet.publicKey, baseMint, quoteMint);
+    const [configPda, __] = await this.findConfigAddress();
     
     // Convert to BN for the contract
     const amountInBN = new BN(amountIn);
@@ -106,17 +576,18 @@ export class BulkerDexClient {
       .swap(
         amountInBN,
         minAmountOutBN,
-        inputToken
+        { [direction === SwapDirection.BaseToQuote ? 'baseToQuote' : 'quoteToBase']: {} },
+        maxPriceImpactBps
       )
       .accounts({
         pool: poolPda,
+        config: configPda,
         oracle: oraclePubkey,
         user: this.wallet.publicKey,
-        authority: authority,
-        userSol: userSol,
-        userUsdc: userUsdc,
-        poolSol: poolSol,
-        poolUsdc: poolUsdc,
+        userBase: userBase,
+        userQuote: userQuote,
+        baseVault: baseVault,
+        quoteVault: quoteVault,
         tokenProgram: TOKEN_PROGRAM_ID,
       })
       .rpc();
@@ -124,6 +595,125 @@ export class BulkerDexClient {
     return txId;
   }
 
+  /**
+   * Swap priced from a signed Pyth Lazer message (Solana format). The message's signature is
+   * checked by an Ed25519 program instruction placed first in the transaction.
+   */
+  async swapLazer(
+    amountIn: number,
+    minAmountOut: number,
+    direction: SwapDirection,
+    baseMint: PublicKey,
+    quoteMint: PublicKey,
+    userBase: PublicKey,
+    userQuote: PublicKey,
+    baseVault: PublicKey,
+    quoteVault: PublicKey,
+    oraclePubkey: PublicKey,
+    lazerMessage: Buffer,
+    maxPriceImpactBps: number = 0 // 0 disables the price impact check
+  ): Promise<string> {
+    const [poolPda, _] = await this.findPoolAddress(this.wallet.publicKey, baseMint, quoteMint);
+    const [configPda, __] = await this.findConfigAddress();
+
+    // Envelope: magic (4), signature (64), signer (32), payload length (2), payload
+    const signature = lazerMessage.subarray(4, 68);
+    const publicKey = lazerMessage.subarray(68, 100);
+    const payloadLen = lazerMessage.readUInt16LE(100);
+    const payload = lazerMessage.subarray(102, 102 + payloadLen);
+    const ed25519Ix = Ed25519Program.createInstructionWithPublicKey({ publicKey, message: payload, signature });
+
+    const txId = await this.program.methods
+      .swapLazer(
+        new BN(amountIn),
+        new BN(minAmountOut),
+        { [direction === SwapDirection.BaseToQuote ? 'baseToQuote' : 'quoteToBase']: {} },
+        maxPriceImpactBps,
+        lazerMessage,
+        0 // index of the Ed25519 instruction
+      )
+      .accounts({
+        swap: {
+          pool: poolPda,
+          config: configPda,
+          oracle: oraclePubkey,
+          user: this.wallet.publicKey,
+          userBase: userBase,
+          userQuote: userQuote,
+          baseVault: baseVault,
+          quoteVault: quoteVault,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        },
+        instructionsSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
+      })
+      .preInstructions([ed25519Ix])
+      .rpc();
+    
+    return txId;
+  }
+
+  /**
+   * Swap for an exact output amount, spending at most maxAmountIn
+   */
+  async swapExactOut(
+    amountOut: number,
+    maxAmountIn: number,
+    direction: SwapDirection,
+    baseMint: PublicKey,
+    quoteMint: PublicKey,
+    userBase: PublicKey,
+    userQuote: PublicKey,
+    baseVault: PublicKey,
+    quoteVault: PublicKey,
+    oraclePubkey: PublicKey,
+    maxPriceImpactBps: number = 0 // 0 disables the price impact check
+  ): Promise<string> {
+    const [poolPda, _] = await this.findPoolAddress(this.wallet.publicKey, baseMint, quoteMint);
+    const [configPda, __] = await this.findConfigAddress();
+    
+    const txId = await this.program.methods
+      .swapExactOut(
+        new BN(amountOut),
+        new BN(maxAmountIn),
+        { [direction === SwapDirection.BaseToQuote ? 'baseToQuote' : 'quoteToBase']: {} },
+        maxPriceImpactBps
+      )
+      .accounts({
+        pool: poolPda,
+        config: configPda,
+        oracle: oraclePubkey,
+        user: this.wallet.publicKey,
+        userBase: userBase,
+        userQuote: userQuote,
+        baseVault: baseVault,
+        quoteVault: quoteVault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+    
+    return txId;
+  }
+
+  /**
+   * Approve the pool PDA to pull a delegated swap's input from the user's token account,
+   * so the swap can be settled without the user signing the settlement transaction
+   */
+  async approveSwapInput(
+    poolPda: PublicKey,
+    userTokenAccount: PublicKey,
+    amount: number
+  ): Promise<string> {
+    const tx = new Transaction().add(
+      createApproveInstruction(
+        userTokenAccount,
+        poolPda,
+        this.wallet.publicKey,
+        BigInt(amount)
+      )
+    );
+    return await this.provider.sendAndConfirm(tx);
+  }
+
   /**
    * Process a delegated swap (called by Magic Block)
    */
@@ -131,15 +721,17 @@ export class BulkerDexClient {
     data: Buffer,
     magicBlockAuthority: PublicKey,
     userPubkey: PublicKey,
-    userSol: PublicKey,
-    userUsdc: PublicKey,
-    poolSol: PublicKey,
-    poolUsdc: PublicKey,
+    baseMint: PublicKey,
+    quoteMint: PublicKey,
+    userBase: PublicKey,
+    userQuote: PublicKey,
+    baseVault: PublicKey,
+    quoteVault: PublicKey,
     oraclePubkey: PublicKey
   ): Promise<string> {
     // Find the pool PDA
-    const [poolPda, _] = await this.findPoolAddress(userPubkey);
-    const [authority, __] = await this.findPoolAuthority(poolPda);
+    const [poolPda, _] = await this.findPoolAddress(userPubkey, baseMint, quoteMint);
+    const [configPda, __] = await this.findConfigAddress();
     
     // Call the process_delegated_swap instruction
     const txId = await this.program.methods
@@ -147,13 +739,13 @@ export class BulkerDexClient {
       .accounts({
         pool: poolPda,
         oracle: oraclePubkey,
+        config: configPda,
         magicBlockAuthority: magicBlockAuthority,
         user: userPubkey,
-        authority: authority,
-        userSol: userSol,
-        userUsdc: userUsdc,
-        poolSol: poolSol,
-        poolUsdc: poolUsdc,
+        userBase: userBase,
+        userQuote: userQuote,
+        baseVault: baseVault,
+        quoteVault: quoteVault,
         tokenProgram: TOKEN_PROGRAM_ID,
       })
       .rpc();
@@ -164,18 +756,49 @@ export class BulkerDexClient {
   /**
    * Get pool information
    */
-  async getPoolInfo(authority: PublicKey): Promise<any> {
-    const [poolPda, _] = await this.findPoolAddress(authority);
+  async getPoolInfo(
+    authority: PublicKey,
+    baseMint: PublicKey,
+    quoteMint: PublicKey
+  ): Promise<any> {
+    const [poolPda, _] = await this.findPoolAddress(authority, baseMint, quoteMint);
     
     try {
       const poolAccount = await this.program.account.pool.fetch(poolPda);
       return {
-        solReserve: poolAccount.solReserve.toNumber(),
-        usdcReserve: poolAccount.usdcReserve.toNumber(),
-        oraclePrice: poolAccount.oraclePrice,
+        baseMint: poolAccount.baseMint.toString(),
+        quoteMint: poolAccount.quoteMint.toString(),
+        baseVault: poolAccount.baseVault.toString(),
+        quoteVault: poolAccount.quoteVault.toString(),
+        baseDecimals: poolAccount.baseDecimals,
+        quoteDecimals: poolAccount.quoteDecimals,
+        oracle: poolAccount.oracle.toString(),
+        baseReserve: poolAccount.baseReserve.toNumber(),
+        quoteReserve: poolAccount.quoteReserve.toNumber(),
+        // Q64.64 fixed-point price, converted to a float for display
+        oraclePrice: poolAccount.oraclePrice.value.shrn(32).toNumber() / 2 ** 32,
         concentrationFactor: poolAccount.concentrationFactor,
         bump: poolAccount.bump,
-        magicBlockDelegate: poolAccount.magicBlockDelegate.toString(),
+        authority: poolAccount.authority.toString(),
+        lpMint: poolAccount.lpMint.toString(),
+        delegated: poolAccount.delegated,
+        maxConfidenceBps: poolAccount.maxConfidenceBps,
+        confidenceMultiplierBps: poolAccount.confidenceMultiplierBps,
+        maxOracleAgeSlots: poolAccount.maxOracleAgeSlots.toNumber(),
+        maxOracleAgeSecs: poolAccount.maxOracleAgeSecs.toNumber(),
+        feeBps: poolAccount.feeBps,
+        minFeeBps: poolAccount.minFeeBps,
+        maxFeeBps: poolAccount.maxFeeBps,
+        volatilityBps: poolAccount.volatilityBps.toNumber(),
+        twapGapBps: poolAccount.twapGapBps.toNumber(),
+        lazerFeedId: poolAccount.lazerFeedId,
+        feesCollectedBase: poolAccount.feesCollectedBase.toNumber(),
+        feesCollectedQuote: poolAccount.feesCollectedQuote.toNumber(),
+        protocolFeesBase: poolAccount.protocolFeesBase.toNumber(),
+        protocolFeesQuote: poolAccount.protocolFeesQuote.toNumber(),
+        paused: poolAccount.paused,
+        breakerThresholdBps: poolAccount.breakerThresholdBps,
+        breakerWindowSlots: poolAccount.breakerWindowSlots.toNumber(),
       };
     } catch (e) {
       console.error('Error fetching pool info:', e);
@@ -186,50 +809,69 @@ export class BulkerDexClient {
   /**
    * Find the pool PDA address
    */
-  async findPoolAddress(authority: PublicKey): Promise<[PublicKey, number]> {
+  async findPoolAddress(
+    authority: PublicKey,
+    baseMint: PublicKey,
+    quoteMint: PublicKey
+  ): Promise<[PublicKey, number]> {
     return await PublicKey.findProgramAddress(
-      [Buffer.from('pool'), authority.toBuffer()],
+      [Buffer.from('pool'), authority.toBuffer(), baseMint.toBuffer(), quoteMint.toBuffer()],
       this.programId
     );
   }
 
   /**
-   * Find the pool authority PDA
+   * Subscribe to program events (SwapExecuted, PoolRebalanced, LiquidityDeposited,
+   * LiquidityWithdrawn, PoolDelegated, FeesClaimed)
    */
-  async findPoolAuthority(poolPda: PublicKey): Promise<[PublicKey, number]> {
+  addEventListener(
+    eventName: string,
+    callback: (event: any, slot: number, signature: string) => void
+  ): number {
+    return this.program.addEventListener(eventName, callback);
+  }
+
+  async removeEventListener(listenerId: number): Promise<void> {
+    await this.program.removeEventListener(listenerId);
+  }
+
+  /**
+   * Find the global config PDA
+   */
+  async findConfigAddress(): Promise<[PublicKey, number]> {
+    return await PublicKey.findProgramAddress(
+      [Buffer.from('config')],
+      this.programId
+    );
+  }
+
+  /**
+   * Find the LP mint PDA for a pool
+   */
+  async findLpMintAddress(poolPda: PublicKey): Promise<[PublicKey, number]> {
     return await PublicKey.findProgramAddress(
-      [poolPda.toBuffer()],
+      [Buffer.from('lp_mint'), poolPda.toBuffer()],
       this.programId
     );
   }
 
   /**
-   * Create token accounts for the pool
+   * Find the pool vault PDAs, token accounts owned by the pool and created at initialization
    */
-  async createTokenAccounts(
+  async findVaultAddresses(
     poolPda: PublicKey,
-    solMint: PublicKey,
-    usdcMint: PublicKey
-  ): Promise<{ poolSol: PublicKey; poolUsdc: PublicKey }> {
-    const [authority, _] = await this.findPoolAuthority(poolPda);
-    
-    // Create SOL token account for the pool
-    const poolSolAccount = await getAssociatedTokenAddress(
-      solMint,
-      authority,
-      true // allowOwnerOffCurve
+    baseMint: PublicKey,
+    quoteMint: PublicKey
+  ): Promise<{ baseVault: PublicKey; quoteVault: PublicKey }> {
+    const [baseVault, _] = await PublicKey.findProgramAddress(
+      [Buffer.from('vault'), poolPda.toBuffer(), baseMint.toBuffer()],
+      this.programId
     );
-    
-    // Create USDC token account for the pool
-    const poolUsdcAccount = await getAssociatedTokenAddress(
-      usdcMint,
-      authority,
-      true // allowOwnerOffCurve
+    const [quoteVault, __] = await PublicKey.findProgramAddress(
+      [Buffer.from('vault'), poolPda.toBuffer(), quoteMint.toBuffer()],
+      this.programId
     );
     
-    return {
-      poolSol: poolSolAccount,
-      poolUsdc: poolUsdcAccount,
-    };
+    return { baseVault, quoteVault };
   }
-} 
\ No newline at end of file
+} 
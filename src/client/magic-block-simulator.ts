@@ -1,6 +1,6 @@
 import { Connection, Keypair, PublicKey } from '@solana/web3.js';
 import { Wallet } from '@project-serum/anchor';
-import { BulkerDexClient, TokenType } from './index';
+import { BulkerDexClient, SwapDirection } from './index';
 import { getAssociatedTokenAddress } from '@solana/spl-token';
 import fs from 'fs';
 import path from 'path';
@@ -27,14 +27,16 @@ const oraclePubkey = new PublicKey('7AxV2515SwLFVxWSpCngQ3TNqY17JERwcCfULc464u7D
 
 // Define the schema for serializing/deserializing the delegated swap data
 class DelegatedSwap {
-  inputToken: number;
+  direction: number;
   amountIn: bigint;
   minAmountOut: bigint;
+  maxPriceImpactBps: number;
 
-  constructor(props: { inputToken: number; amountIn: bigint; minAmountOut: bigint }) {
-    this.inputToken = props.inputToken;
+  constructor(props: { direction: number; amountIn: bigint; minAmountOut: bigint; maxPriceImpactBps: number }) {
+    this.direction = props.direction;
     this.amountIn = props.amountIn;
     this.minAmountOut = props.minAmountOut;
+    this.maxPriceImpactBps = props.maxPriceImpactBps;
   }
 }
 
@@ -44,9 +46,10 @@ const DelegatedSwapSchema = new Map([
     {
       kind: 'struct',
       fields: [
-        ['inputToken', 'u8'],
+        ['direction', 'u8'],
         ['amountIn', 'u64'],
         ['minAmountOut', 'u64'],
+        ['maxPriceImpactBps', 'u16'],
       ],
     },
   ],
@@ -60,36 +63,37 @@ async function main() {
   const userPubkey = new PublicKey('USER_PUBLIC_KEY_HERE');
   
   // Token accounts
-  const solMint = new PublicKey('SOL_MINT_HERE');
-  const usdcMint = new PublicKey('USDC_MINT_HERE');
+  const baseMint = new PublicKey('BASE_MINT_HERE');
+  const quoteMint = new PublicKey('QUOTE_MINT_HERE');
   
-  const userSolAccount = await getAssociatedTokenAddress(
-    solMint,
+  const userBaseAccount = await getAssociatedTokenAddress(
+    baseMint,
     userPubkey,
     false
   );
   
-  const userUsdcAccount = await getAssociatedTokenAddress(
-    usdcMint,
+  const userQuoteAccount = await getAssociatedTokenAddress(
+    quoteMint,
     userPubkey,
     false
   );
   
   // Find the pool PDA
-  const [poolPda, _] = await client.findPoolAddress(userPubkey);
+  const [poolPda, _] = await client.findPoolAddress(userPubkey, baseMint, quoteMint);
   
   // Get pool token accounts
-  const { poolSol, poolUsdc } = await client.createTokenAccounts(
+  const { baseVault, quoteVault } = await client.findVaultAddresses(
     poolPda,
-    solMint,
-    usdcMint
+    baseMint,
+    quoteMint
   );
   
   // Create the delegated swap data
   const delegatedSwap = new DelegatedSwap({
-    inputToken: TokenType.SOL,
+    direction: SwapDirection.BaseToQuote,
     amountIn: BigInt(50000000),  // 0.05 SOL
     minAmountOut: BigInt(4500000), // 4.5 USDC
+    maxPriceImpactBps: 200,        // Abort if filled more than 2% below the oracle
   });
   
   // Serialize the data
@@ -102,10 +106,12 @@ async function main() {
     data,
     magicBlockKeypair.publicKey,
     userPubkey,
-    userSolAccount,
-    userUsdcAccount,
-    poolSol,
-    poolUsdc,
+    baseMint,
+    quoteMint,
+    userBaseAccount,
+    userQuoteAccount,
+    baseVault,
+    quoteVault,
     oraclePubkey
   );
   
@@ -1,9 +1,10 @@
 import { Connection, Keypair, PublicKey } from '@solana/web3.js';
 import { Wallet } from '@project-serum/anchor';
-import { BulkerDexClient, TokenType } from './index';
+import { BulkerDexClient, SwapDirection } from './index';
 import { 
   TOKEN_PROGRAM_ID, 
   getAssociatedTokenAddress, 
+  getOrCreateAssociatedTokenAccount,
   createMint 
 } from '@solana/spl-token';
 import fs from 'fs';
@@ -28,6 +29,9 @@ const programId = new PublicKey('BuLKerDex1111111111111111111111111111111111');
 // Magic Block authority - replace with the actual Magic Block authority
 const magicBlockAuth = new PublicKey('MBLKhRxBCK7vLxaTpPDbzZsLTJqBhgUJ8bM6jE7ACS9');
 
+// Pyth oracle program that owns price accounts
+const pythOracleProgram = new PublicKey('FsJ3A3u2vn5cTVofAjvy6y5kwABJAqYWpe4975bi2epH');
+
 // Pyth oracle account for SOL/USD - replace with the actual oracle account
 const oraclePubkey = new PublicKey('7AxV2515SwLFVxWSpCngQ3TNqY17JERwcCfULc464u7D');
 
@@ -53,27 +57,61 @@ async function main() {
     false // allowOwnerOffCurve
   );
   
-  // Initialize the pool
+  // Initialize the global config, with the wallet as admin and treasury; the wallet must be
+  // the program's upgrade authority
+  console.log('Initializing config...');
+  await client.initializeConfig({
+    treasury: keypair.publicKey,
+    protocolFeeBps: 2000,          // Protocol share of swap fees (20%)
+    oraclePrograms: [pythOracleProgram],
+    magicBlockAuthority: magicBlockAuth,
+    guardian: keypair.publicKey,
+    defaultFeeBps: 30,             // Default swap fee (0.3%)
+    restrictPoolCreation: false,
+  });
+  
+  // Initialize the pool; its vaults are created as pool-owned PDAs
   console.log('Initializing pool...');
   const poolPda = await client.initializePool(
-    1000000000, // 1 SOL (in lamports)
-    100000000,  // 100 USDC (in micro-USDC)
+    solMint,
+    usdcMint,
+    oraclePubkey,
     5,          // Concentration factor
-    magicBlockAuth
+    200,        // Max oracle confidence (2%)
+    10000,      // Confidence spread multiplier (1.0x)
+    20,         // Max oracle age in slots
+    10,         // Max oracle age in seconds
+    null        // Use the config's default swap fee
   );
   console.log('Pool initialized:', poolPda.toString());
-  
-  // Create pool token accounts
-  console.log('Creating pool token accounts...');
-  const { poolSol, poolUsdc } = await client.createTokenAccounts(
+  const { baseVault, quoteVault } = await client.findVaultAddresses(
     poolPda,
     solMint,
     usdcMint
   );
   
+  // Seed the pool with liquidity
+  console.log('Depositing liquidity...');
+  const [lpMint, _] = await client.findLpMintAddress(poolPda);
+  const userLp = await getOrCreateAssociatedTokenAccount(connection, keypair, lpMint, keypair.publicKey);
+  await client.depositLiquidity(
+    keypair.publicKey,
+    solMint,
+    usdcMint,
+    1000000000, // 1 SOL (in lamports)
+    100000000,  // 100 USDC (in micro-USDC)
+    0,          // Minimum LP shares
+    userSolAccount,
+    userUsdcAccount,
+    userLp.address,
+    baseVault,
+    quoteVault,
+    oraclePubkey
+  );
+  
   // Get pool info
   console.log('Getting pool info...');
-  const poolInfo = await client.getPoolInfo(keypair.publicKey);
+  const poolInfo = await client.getPoolInfo(keypair.publicKey, solMint, usdcMint);
   console.log('Pool info:', poolInfo);
   
   // Perform a swap
@@ -81,18 +119,21 @@ async function main() {
   const txId = await client.swap(
     50000000,  // 0.05 SOL (in lamports)
     4500000,   // Minimum 4.5 USDC (in micro-USDC)
-    TokenType.SOL,
+    SwapDirection.BaseToQuote,
+    solMint,
+    usdcMint,
     userSolAccount,
     userUsdcAccount,
-    poolSol,
-    poolUsdc,
-    oraclePubkey
+    baseVault,
+    quoteVault,
+    oraclePubkey,
+    100        // Max 1% below the oracle price
   );
   console.log('Swap transaction:', txId);
   
   // Get updated pool info
   console.log('Getting updated pool info...');
-  const updatedPoolInfo = await client.getPoolInfo(keypair.publicKey);
+  const updatedPoolInfo = await client.getPoolInfo(keypair.publicKey, solMint, usdcMint);
   console.log('Updated pool info:', updatedPoolInfo);
 }
 
@@ -0,0 +1,119 @@
+use anchor_lang::prelude::*;
+use crate::{error::DexError, fixed_point::BPS_DENOMINATOR, pool::MAX_FEE_BPS};
+
+// Maximum number of oracle programs a config can allow
+pub const MAX_ORACLE_PROGRAMS: usize = 4;
+
+// Maximum number of Pyth Lazer signing keys a config can trust
+pub const MAX_TRUSTED_SIGNERS: usize = 4;
+
+// Operations that can be paused, as bits of `Config::paused` and `Pool::paused`
+pub const PAUSE_SWAP: u8 = 1 << 0;
+pub const PAUSE_DEPOSIT: u8 = 1 << 1;
+pub const PAUSE_WITHDRAW: u8 = 1 << 2;
+pub const PAUSE_DELEGATION: u8 = 1 << 3;
+pub const PAUSE_ALL: u8 = PAUSE_SWAP | PAUSE_DEPOSIT | PAUSE_WITHDRAW | PAUSE_DELEGATION;
+
+pub fn validate_pause_flags(paused: u8) -> Result<()> {
+    if paused & !PAUSE_ALL != 0 {
+        msg!("Unknown pause flags: {:#010b}", paused);
+        return Err(error!(DexError::InvalidPauseFlags));
+    }
+    Ok(())
+}
+
+// The config can only be created by the program's upgrade authority; otherwise whoever
+// called initialize_config first after deploy would become admin
+pub fn check_upgrade_authority(program_data: &ProgramData, signer: &Pubkey) -> Result<()> {
+    if program_data.upgrade_authority_address != Some(*signer) {
+        msg!("Config must be initialized by the upgrade authority {:?}, not {}",
+            program_data.upgrade_authority_address, signer);
+        return Err(error!(DexError::Unauthorized));
+    }
+    Ok(())
+}
+
+// Settings an admin can set through `initialize_config` / `update_config`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ConfigParams {
+    pub treasury: Pubkey,
+    pub protocol_fee_bps: u16,
+    pub oracle_programs: Vec<Pubkey>,
+    pub magic_block_authority: Pubkey,
+    pub guardian: Pubkey,
+    pub default_fee_bps: u16,
+    pub restrict_pool_creation: bool,
+}
+
+// Protocol-wide settings shared by every pool
+#[account]
+#[derive(Default)]
+pub struct Config {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,          // Set by `transfer_admin`, default when no transfer is pending
+    pub treasury: Pubkey,               // Owner of the token accounts protocol fees are claimed to
+    pub protocol_fee_bps: u16,          // Share of each swap fee kept by the protocol, 10_000 = all of it
+    pub oracle_programs: Vec<Pubkey>,   // Programs allowed to own pool price accounts
+    pub magic_block_authority: Pubkey,  // Ephemeral validator allowed to process delegated swaps
+    pub default_fee_bps: u16,           // Swap fee for pools created without an explicit fee
+    pub restrict_pool_creation: bool,   // Only the admin may create pools when set
+    pub guardian: Pubkey,               // Key allowed to pause and unpause operations
+    pub paused: u8,                     // Operations paused for every pool, see PAUSE_*
+    pub bump: u8,
+    pub trusted_signers: Vec<Pubkey>,   // Pyth Lazer signing keys, empty disables Lazer pricing
+}
+
+impl Config {
+    pub const LEN: usize = 32 + 32 + 32 + 2 + (4 + 32 * MAX_ORACLE_PROGRAMS) + 32 + 2 + 1 + 32 + 1 + 1 + (4 + 32 * MAX_TRUSTED_SIGNERS); // size of each field
+
+    pub fn apply_params(&mut self, params: ConfigParams) -> Result<()> {
+        if params.protocol_fee_bps as u64 > BPS_DENOMINATOR {
+            msg!("Protocol fee share of {} bps exceeds 100%", params.protocol_fee_bps);
+            return Err(error!(DexError::InvalidFeeBps));
+        }
+        if params.default_fee_bps > MAX_FEE_BPS {
+            msg!("Default fee of {} bps exceeds maximum of {} bps", params.default_fee_bps, MAX_FEE_BPS);
+            return Err(error!(DexError::InvalidFeeBps));
+        }
+        if params.oracle_programs.is_empty() || params.oracle_programs.len() > MAX_ORACLE_PROGRAMS {
+            msg!("Expected 1 to {} oracle programs, got {}",
+                MAX_ORACLE_PROGRAMS, params.oracle_programs.len());
+            return Err(error!(DexError::InvalidOracleProgramList));
+        }
+
+        self.treasury = params.treasury;
+        self.protocol_fee_bps = params.protocol_fee_bps;
+        self.oracle_programs = params.oracle_programs;
+        self.magic_block_authority = params.magic_block_authority;
+        self.default_fee_bps = params.default_fee_bps;
+        self.restrict_pool_creation = params.restrict_pool_creation;
+        self.guardian = params.guardian;
+        Ok(())
+    }
+
+    pub fn set_trusted_signers(&mut self, trusted_signers: Vec<Pubkey>) -> Result<()> {
+        if trusted_signers.len() > MAX_TRUSTED_SIGNERS || trusted_signers.contains(&Pubkey::default()) {
+            msg!("Expected up to {} non-default Lazer signers, got {:?}",
+                MAX_TRUSTED_SIGNERS, trusted_signers);
+            return Err(error!(DexError::InvalidTrustedSignerList));
+        }
+        self.trusted_signers = trusted_signers;
+        Ok(())
+    }
+
+    // First step of an admin transfer; the current admin stays in charge until accepted
+    pub fn propose_admin(&mut self, new_admin: Pubkey) {
+        self.pending_admin = new_admin;
+    }
+
+    // Second step: only the proposed key can take over, and only once
+    pub fn accept_admin(&mut self, new_admin: &Pubkey) -> Result<()> {
+        if self.pending_admin == Pubkey::default() || self.pending_admin != *new_admin {
+            msg!("{} is not the pending admin", new_admin);
+            return Err(error!(DexError::Unauthorized));
+        }
+        self.admin = self.pending_admin;
+        self.pending_admin = Pubkey::default();
+        Ok(())
+    }
+}
@@ -0,0 +1,164 @@
+use anchor_lang::prelude::*;
+use crate::{
+    error::DexError,
+    fixed_point::BPS_DENOMINATOR,
+    pool::{Pool, SwapDirection},
+};
+
+// Upper bound on the base spread of an oracle-anchored pool (10%)
+pub const MAX_BASE_SPREAD_BPS: u16 = 1_000;
+
+// Pricing formula a pool quotes swaps with, chosen per pool at creation
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum CurveType {
+    // Constant product on the concentrated curve reserves, re-centered on rebalance
+    #[default]
+    ConstantProduct,
+    // Oracle mid price less a base spread, an inventory skew term and a size-dependent impact
+    OracleAnchored,
+}
+
+impl Pool {
+    pub fn set_curve(&mut self, curve_type: CurveType, base_spread_bps: u16) -> Result<()> {
+        if base_spread_bps > MAX_BASE_SPREAD_BPS {
+            msg!("Base spread of {} bps exceeds maximum of {} bps", base_spread_bps, MAX_BASE_SPREAD_BPS);
+            return Err(error!(DexError::InvalidCurveParams));
+        }
+        self.curve_type = curve_type;
+        self.base_spread_bps = base_spread_bps;
+        Ok(())
+    }
+
+    // Curve output for an input the fee has already been taken from
+    pub(crate) fn curve_output(&self, direction: SwapDirection, amount_in: u128) -> Result<u128> {
+        match self.curve_type {
+            CurveType::ConstantProduct => self.constant_product_output(direction, amount_in),
+            CurveType::OracleAnchored => self.oracle_anchored_output(direction, amount_in),
+        }
+    }
+
+    // Smallest fee-free input whose curve output covers `amount_out`, rounded up
+    pub(crate) fn curve_input(&self, direction: SwapDirection, amount_out: u128) -> Result<u128> {
+        match self.curve_type {
+            CurveType::ConstantProduct => self.constant_product_input(direction, amount_out),
+            CurveType::OracleAnchored => self.oracle_anchored_input(direction, amount_out),
+        }
+    }
+
+    // (input, output) reserves of the concentrated curve
+    fn curve_reserves(&self, direction: SwapDirection) -> (u128, u128) {
+        let (reserve_in, reserve_out) = match direction {
+            SwapDirection::BaseToQuote => (self.curve_base_reserve, self.curve_quote_reserve),
+            SwapDirection::QuoteToBase => (self.curve_quote_reserve, self.curve_base_reserve),
+        };
+        (reserve_in as u128, reserve_out as u128)
+    }
+
+    fn constant_product_output(&self, direction: SwapDirection, amount_in: u128) -> Result<u128> {
+        let (curve_in, curve_out) = self.curve_reserves(direction);
+        let numerator = amount_in.checked_mul(curve_out)
+            .ok_or(error!(DexError::ArithmeticOverflow))?;
+        let denominator = curve_in.checked_add(amount_in)
+            .ok_or(error!(DexError::ArithmeticOverflow))?;
+        numerator.checked_div(denominator)
+            .ok_or(error!(DexError::ArithmeticOverflow))
+    }
+
+    fn constant_product_input(&self, direction: SwapDirection, amount_out: u128) -> Result<u128> {
+        let (curve_in, curve_out) = self.curve_reserves(direction);
+        if amount_out >= curve_out {
+            msg!("Requested output {} exceeds curve reserve {}", amount_out, curve_out);
+            return Err(error!(DexError::InsufficientLiquidity));
+        }
+
+        let numerator = amount_out.checked_mul(curve_in)
+            .ok_or(error!(DexError::ArithmeticOverflow))?;
+        let denominator = curve_out - amount_out;
+        Ok(numerator.checked_add(denominator - 1)
+            .ok_or(error!(DexError::ArithmeticOverflow))?
+            / denominator)
+    }
+
+    // Spread in bps before size impact: the base spread, widened for trades that push
+    // inventory further from target and narrowed for trades that pull it back
+    pub fn anchored_spread_bps(&self, direction: SwapDirection) -> Result<u64> {
+        let (skew_bps, worsens_skew) = self.inventory_skew_bps(direction)?;
+        // Deeper pools tolerate more skew
+        let skew_bps = skew_bps / (self.concentration_factor.max(1) as u64);
+
+        let base_spread_bps = self.base_spread_bps as u64;
+        Ok(if worsens_skew {
+            base_spread_bps + skew_bps
+        } else {
+            base_spread_bps.saturating_sub(skew_bps)
+        })
+    }
+
+    // Pool value in the output token, amplified by the concentration factor. A trade worth
+    // the whole depth would pay half its fair value.
+    fn anchored_depth(&self, direction: SwapDirection) -> Result<u128> {
+        let value = self.value_in_quote(self.base_reserve, self.quote_reserve)?;
+        let value = match direction {
+            SwapDirection::BaseToQuote => value,
+            SwapDirection::QuoteToBase => {
+                let value = u64::try_from(value).map_err(|_| error!(DexError::ArithmeticOverflow))?;
+                self.oracle_price.div_u64(value)? as u128
+            }
+        };
+        value.checked_mul(self.concentration_factor as u128)
+            .ok_or(error!(DexError::ArithmeticOverflow))
+    }
+
+    // out = fair * (1 - spread) * depth / (depth + fair), with fair the output at the oracle mid
+    fn oracle_anchored_output(&self, direction: SwapDirection, amount_in: u128) -> Result<u128> {
+        let amount_in = u64::try_from(amount_in).map_err(|_| error!(DexError::ArithmeticOverflow))?;
+        let fair_out = match direction {
+            SwapDirection::BaseToQuote => self.oracle_price.mul_u64(amount_in)?,
+            SwapDirection::QuoteToBase => self.oracle_price.div_u64(amount_in)?,
+        } as u128;
+        let keep_bps = BPS_DENOMINATOR.saturating_sub(self.anchored_spread_bps(direction)?) as u128;
+        let depth = self.anchored_depth(direction)?;
+
+        let numerator = fair_out
+            .checked_mul(keep_bps)
+            .and_then(|n| n.checked_mul(depth))
+            .ok_or(error!(DexError::ArithmeticOverflow))?;
+        let denominator = depth
+            .checked_add(fair_out)
+            .and_then(|d| d.checked_mul(BPS_DENOMINATOR as u128))
+            .ok_or(error!(DexError::ArithmeticOverflow))?;
+        Ok(numerator.checked_div(denominator).unwrap_or(0))
+    }
+
+    // Inverts oracle_anchored_output for the fair output, then converts it to an input at the mid
+    fn oracle_anchored_input(&self, direction: SwapDirection, amount_out: u128) -> Result<u128> {
+        let keep_bps = BPS_DENOMINATOR.saturating_sub(self.anchored_spread_bps(direction)?) as u128;
+        let depth = self.anchored_depth(direction)?;
+
+        // fair >= out * B * depth / (keep * depth - out * B)
+        let out_bps = amount_out
+            .checked_mul(BPS_DENOMINATOR as u128)
+            .ok_or(error!(DexError::ArithmeticOverflow))?;
+        let capacity = keep_bps
+            .checked_mul(depth)
+            .ok_or(error!(DexError::ArithmeticOverflow))?;
+        if out_bps >= capacity {
+            msg!("Requested output {} exceeds what the anchored curve can pay", amount_out);
+            return Err(error!(DexError::InsufficientLiquidity));
+        }
+        let numerator = out_bps
+            .checked_mul(depth)
+            .ok_or(error!(DexError::ArithmeticOverflow))?;
+        let denominator = capacity - out_bps;
+        let fair_out = numerator.checked_add(denominator - 1)
+            .ok_or(error!(DexError::ArithmeticOverflow))?
+            / denominator;
+
+        let fair_out = u64::try_from(fair_out).map_err(|_| error!(DexError::ArithmeticOverflow))?;
+        let amount_in = match direction {
+            SwapDirection::BaseToQuote => self.oracle_price.div_u64_ceil(fair_out)?,
+            SwapDirection::QuoteToBase => self.oracle_price.mul_u64_ceil(fair_out)?,
+        };
+        Ok(amount_in as u128)
+    }
+}
@@ -1,20 +1,12 @@
 use anchor_lang::prelude::*;
-use ephemeral_rollups_sdk::anchor::delegate;
+use ephemeral_rollups_sdk::anchor::{commit, delegate};
 use ephemeral_rollups_sdk::cpi::DelegateAccounts;
 use ephemeral_rollups_sdk::cpi::DelegateConfig;
-use crate::{pool::TokenType, error::DexError};
+use crate::{config::Config, pool::{Pool, SwapParams}, error::DexError};
 
 // Magic Block program ID
 pub const MAGIC_BLOCK_PROGRAM_ID: &str = "MBLKhRxBCK7vLxaTpPDbzZsLTJqBhgUJ8bM6jE7ACS9";
 
-// Data structure for delegated transactions
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct DelegatedSwap {
-    pub input_token: TokenType,
-    pub amount_in: u64,
-    pub min_amount_out: u64,
-}
-
 // Function to validate magic block authority
 pub fn validate_magic_block_authority(
     authority: &Pubkey, 
@@ -30,16 +22,47 @@ pub fn validate_magic_block_authority(
 }
 
 // Function to decode delegated swap data from Magic Block
-pub fn decode_delegated_swap(data: &[u8]) -> Result<DelegatedSwap> {
-    DelegatedSwap::try_from_slice(data)
+pub fn decode_delegated_swap(data: &[u8]) -> Result<SwapParams> {
+    SwapParams::try_from_slice(data)
         .map_err(|_| error!(DexError::InvalidInstruction))
 }
 
+// Function to flag a pool as delegated directly in its account data.
+// Used before the delegation CPI, after which the program no longer owns the account.
+pub fn set_pool_delegated(pool_info: &AccountInfo, delegated: bool) -> Result<Pool> {
+    if pool_info.owner != &crate::ID {
+        return Err(error!(ErrorCode::AccountOwnedByWrongProgram));
+    }
+    let mut data = pool_info.try_borrow_mut_data()?;
+    let mut pool = Pool::try_deserialize(&mut &data[..])?;
+    pool.delegated = delegated;
+    let mut dst: &mut [u8] = &mut data;
+    pool.try_serialize(&mut dst)?;
+    Ok(pool)
+}
+
 // Define an accounts struct for delegate operations
 #[delegate]
 #[derive(Accounts)]
 pub struct DelegateSwap<'info> {
     pub payer: Signer<'info>,
+    /// CHECK: The pool PDA, deserialized and checked against the payer before delegation
     #[account(mut, del)]
-    pub pool: Account<'info, crate::pool::Pool>,
+    pub pool: AccountInfo<'info>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+}
+
+// Accounts struct for committing and undelegating the pool from the ephemeral rollup
+#[commit]
+#[derive(Accounts)]
+pub struct UndelegatePool<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"pool", payer.key().as_ref(), pool.base_mint.as_ref(), pool.quote_mint.as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
 } 
\ No newline at end of file
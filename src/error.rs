@@ -24,4 +24,68 @@ pub enum DexError {
     TokenTransferFailed,
     #[msg("Invalid oracle data (price too old)")]
     InvalidOracleData,
+    #[msg("Deposit amount must be greater than zero")]
+    InvalidDepositAmount,
+    #[msg("Insufficient pool liquidity")]
+    InsufficientLiquidity,
+    #[msg("Invalid pool vault account")]
+    InvalidVaultAccount,
+    #[msg("Withdraw amount must be greater than zero")]
+    InvalidWithdrawAmount,
+    #[msg("Pool is delegated to the ephemeral rollup")]
+    PoolDelegated,
+    #[msg("Malformed Pyth Lazer message")]
+    InvalidLazerMessage,
+    #[msg("Oracle message signer is not trusted")]
+    UntrustedOracleSigner,
+    #[msg("Missing or mismatched Ed25519 signature instruction")]
+    InvalidSignatureInstruction,
+    #[msg("Requested feed not present in oracle message")]
+    OracleFeedNotFound,
+    #[msg("Oracle account has an invalid magic number")]
+    InvalidOracleMagic,
+    #[msg("Unsupported oracle account version")]
+    InvalidOracleVersion,
+    #[msg("Oracle account is not a price account")]
+    InvalidOracleAccountType,
+    #[msg("Unsupported oracle price type")]
+    InvalidOraclePriceType,
+    #[msg("Oracle price is not trading")]
+    OracleNotTrading,
+    #[msg("Oracle price must be positive")]
+    InvalidOraclePrice,
+    #[msg("Oracle account is not owned by the oracle program")]
+    InvalidOracleOwner,
+    #[msg("Oracle confidence interval is too wide")]
+    OracleConfidenceTooWide,
+    #[msg("Token mint does not match the pool")]
+    InvalidMint,
+    #[msg("Signer is not authorized for this pool")]
+    Unauthorized,
+    #[msg("Fee exceeds the maximum allowed")]
+    InvalidFeeBps,
+    #[msg("Oracle program list is empty or too long")]
+    InvalidOracleProgramList,
+    #[msg("Operation is paused for this pool")]
+    PoolPaused,
+    #[msg("Unknown pause flags")]
+    InvalidPauseFlags,
+    #[msg("User has not authorized the input transfer")]
+    InputNotAuthorized,
+    #[msg("Vault balance is below the pool's accounted reserves")]
+    VaultBelowReserves,
+    #[msg("Price impact exceeds the swap's limit")]
+    PriceImpactExceeded,
+    #[msg("Invalid rebalance target or cap")]
+    InvalidRebalanceParams,
+    #[msg("Invalid curve type or spread")]
+    InvalidCurveParams,
+    #[msg("Trusted signer list is too long or holds the default key")]
+    InvalidTrustedSignerList,
+    #[msg("Lazer price is not newer than the last one used")]
+    StaleLazerPrice,
+    #[msg("Oracle moved past the circuit breaker threshold")]
+    CircuitBreakerTripped,
+    #[msg("Oracle price is within the circuit breaker threshold")]
+    CircuitBreakerNotTripped,
 } 
\ No newline at end of file
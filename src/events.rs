@@ -0,0 +1,70 @@
+use anchor_lang::prelude::*;
+use crate::{fixed_point::FixedPrice, pool::SwapDirection};
+
+#[event]
+pub struct SwapExecuted {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub direction: SwapDirection,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee: u64,
+    pub oracle_price: FixedPrice,   // Quote atoms per base atom
+    pub slot: u64,
+}
+
+#[event]
+pub struct PoolRebalanced {
+    pub pool: Pubkey,
+    pub old_price: FixedPrice,
+    pub new_price: FixedPrice,
+    pub curve_price: FixedPrice,    // Curve center after the inventory skew adjustment
+    pub change_bps: u64,
+    pub base_reserve: u64,
+    pub quote_reserve: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct CircuitBreakerTripped {
+    pub pool: Pubkey,
+    pub price: FixedPrice,          // Oracle price that tripped the breaker
+    pub reference: FixedPrice,      // Price at the start of the breaker window
+    pub slot: u64,
+}
+
+#[event]
+pub struct LiquidityDeposited {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub base_amount: u64,
+    pub quote_amount: u64,
+    pub lp_amount: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct LiquidityWithdrawn {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub base_amount: u64,
+    pub quote_amount: u64,
+    pub lp_amount: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct PoolDelegated {
+    pub pool: Pubkey,
+    pub authority: Pubkey,
+    pub slot: u64,
+}
+
+#[event]
+pub struct FeesClaimed {
+    pub pool: Pubkey,
+    pub treasury: Pubkey,
+    pub base_amount: u64,
+    pub quote_amount: u64,
+    pub slot: u64,
+}
@@ -0,0 +1,116 @@
+use anchor_lang::prelude::*;
+use crate::{
+    error::DexError,
+    fixed_point::{FixedPrice, BPS_DENOMINATOR},
+    oracle::OraclePrice,
+    pool::{Pool, SwapDirection, MAX_FEE_BPS},
+};
+
+// Inventory skew (in bps of pool value) that moves the fee by 1 bps
+pub const SKEW_FEE_DIVISOR: u64 = 100;
+
+// Annualized volatility (in bps) that adds 1 bps to the fee: 100% volatility adds 50 bps
+pub const VOLATILITY_FEE_DIVISOR: u64 = 200;
+
+// Share of the gap between the oracle price and its TWAP added to the fee, in bps
+pub const TWAP_GAP_FEE_SHARE_BPS: u64 = 5_000;
+
+// Weight of the newest sample in the volatility moving average, in bps
+pub const VOLATILITY_EWMA_WEIGHT_BPS: u64 = 2_000;
+
+// 400ms slots, used to annualize the moves between samples
+pub const SLOTS_PER_YEAR: u64 = 78_840_000;
+
+const PPM_DENOMINATOR: u64 = 1_000_000;
+
+impl Pool {
+    // Bounds for the dynamic fee; a max of 0 disables it and charges fee_bps flat
+    pub fn set_fee_bounds(&mut self, min_fee_bps: u16, max_fee_bps: u16) -> Result<()> {
+        if min_fee_bps > max_fee_bps || max_fee_bps > MAX_FEE_BPS {
+            msg!("Invalid fee bounds {}-{} bps (max {} bps)", min_fee_bps, max_fee_bps, MAX_FEE_BPS);
+            return Err(error!(DexError::InvalidFeeBps));
+        }
+        self.min_fee_bps = min_fee_bps;
+        self.max_fee_bps = max_fee_bps;
+        Ok(())
+    }
+
+    // Fee for a swap in `direction`: fee_bps, raised for trades that push inventory
+    // further from target and lowered for trades that restore it, plus a volatility
+    // premium, clamped to the pool's bounds
+    pub fn dynamic_fee_bps(&self, direction: SwapDirection) -> Result<u64> {
+        if self.max_fee_bps == 0 {
+            return Ok(self.fee_bps as u64);
+        }
+
+        let (skew_bps, worsens_skew) = self.inventory_skew_bps(direction)?;
+        let skew_fee_bps = skew_bps / SKEW_FEE_DIVISOR;
+        let fee_bps = if worsens_skew {
+            self.fee_bps as u64 + skew_fee_bps
+        } else {
+            (self.fee_bps as u64).saturating_sub(skew_fee_bps)
+        };
+
+        Ok(fee_bps
+            .saturating_add(self.volatility_fee_bps())
+            .clamp(self.min_fee_bps as u64, self.max_fee_bps as u64))
+    }
+
+    // Premium for volatile markets, where arbitrageurs pick off stale quotes: scales with
+    // the pool's moving average of annualized volatility and with how far the price has
+    // run from its TWAP
+    pub fn volatility_fee_bps(&self) -> u64 {
+        let twap_gap_fee_bps = self.twap_gap_bps.saturating_mul(TWAP_GAP_FEE_SHARE_BPS) / BPS_DENOMINATOR;
+        (self.volatility_bps / VOLATILITY_FEE_DIVISOR).saturating_add(twap_gap_fee_bps)
+    }
+
+    // Record the feed's TWAP gap, and fold the oracle move since the last
+    // sample into the moving average. Sampled at most once per slot so repeated swaps on
+    // one price do not dilute it.
+    pub fn record_volatility(&mut self, oracle_price: &OraclePrice, slot: u64) -> Result<()> {
+        let price = oracle_price.price;
+        self.twap_gap_bps = match oracle_price.twap {
+            Some(twap) if !twap.is_zero() => twap.change_bps(&price)?,
+            _ => 0,
+        };
+
+        if self.volatility_ref_price.is_zero() {
+            self.volatility_ref_price = price;
+            self.volatility_ref_slot = slot;
+            return Ok(());
+        }
+        if slot <= self.volatility_ref_slot {
+            return Ok(());
+        }
+
+        // Moves beyond 100% count as 100%
+        let diff = price.max(self.volatility_ref_price).checked_sub(price.min(self.volatility_ref_price))?;
+        let move_ppm = FixedPrice::mul_ratio_u64(PPM_DENOMINATOR, &diff, &self.volatility_ref_price)?
+            .min(PPM_DENOMINATOR);
+        // Mean absolute move scaled by sqrt(time), slightly under the standard deviation
+        let elapsed_slots = slot - self.volatility_ref_slot;
+        let sample_bps = move_ppm * isqrt(SLOTS_PER_YEAR / elapsed_slots).max(1)
+            / (PPM_DENOMINATOR / BPS_DENOMINATOR);
+
+        self.volatility_bps = (sample_bps * VOLATILITY_EWMA_WEIGHT_BPS
+            + self.volatility_bps * (BPS_DENOMINATOR - VOLATILITY_EWMA_WEIGHT_BPS))
+            / BPS_DENOMINATOR;
+        self.volatility_ref_price = price;
+        self.volatility_ref_slot = slot;
+        Ok(())
+    }
+}
+
+// Integer square root, rounded down
+fn isqrt(n: u64) -> u64 {
+    if n < 2 {
+        return n;
+    }
+    let mut x = n;
+    let mut y = (x + 1) / 2;
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x
+}
@@ -0,0 +1,184 @@
+use anchor_lang::prelude::*;
+use std::fmt;
+use crate::error::DexError;
+
+// Number of fractional bits in the Q64.64 representation
+pub const FRAC_BITS: u32 = 64;
+const FRAC_MASK: u128 = (1u128 << FRAC_BITS) - 1;
+
+// Basis points in 100%
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+// Unsigned Q64.64 fixed-point price: 64 integer bits and 64 fractional bits.
+// Used instead of f64 so price math is deterministic on BPF and overflow is explicit.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
+pub struct FixedPrice {
+    pub value: u128,
+}
+
+impl FixedPrice {
+    pub const ZERO: FixedPrice = FixedPrice { value: 0 };
+    pub const ONE: FixedPrice = FixedPrice { value: 1u128 << FRAC_BITS };
+
+    pub fn from_int(n: u64) -> Self {
+        FixedPrice { value: (n as u128) << FRAC_BITS }
+    }
+
+    // Exact ratio `num / den`, rounded down
+    pub fn from_ratio(num: u64, den: u64) -> Result<Self> {
+        let value = ((num as u128) << FRAC_BITS)
+            .checked_div(den as u128)
+            .ok_or(error!(DexError::ArithmeticOverflow))?;
+        Ok(FixedPrice { value })
+    }
+
+    // Convert a Pyth-style `mantissa * 10^expo` into fixed point, rounded down
+    pub fn from_decimal(mantissa: u64, expo: i32) -> Result<Self> {
+        let scale = 10u128
+            .checked_pow(expo.unsigned_abs())
+            .ok_or(error!(DexError::ArithmeticOverflow))?;
+
+        let value = if expo >= 0 {
+            let int = (mantissa as u128)
+                .checked_mul(scale)
+                .ok_or(error!(DexError::ArithmeticOverflow))?;
+            if int > u64::MAX as u128 {
+                return Err(error!(DexError::ArithmeticOverflow));
+            }
+            int << FRAC_BITS
+        } else {
+            ((mantissa as u128) << FRAC_BITS) / scale
+        };
+
+        Ok(FixedPrice { value })
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.value == 0
+    }
+
+    // `amount * price`, rounded down
+    pub fn mul_u64(&self, amount: u64) -> Result<u64> {
+        // Split into integer and fractional parts so neither product can exceed u128
+        let int_part = (amount as u128) * (self.value >> FRAC_BITS);
+        let frac_part = ((amount as u128) * (self.value & FRAC_MASK)) >> FRAC_BITS;
+        let result = int_part
+            .checked_add(frac_part)
+            .ok_or(error!(DexError::ArithmeticOverflow))?;
+        u64::try_from(result).map_err(|_| error!(DexError::ArithmeticOverflow))
+    }
+
+    // `amount * price`, rounded up
+    pub fn mul_u64_ceil(&self, amount: u64) -> Result<u64> {
+        let floor = self.mul_u64(amount)?;
+        let remainder = ((amount as u128) * (self.value & FRAC_MASK)) & FRAC_MASK;
+        if remainder == 0 {
+            Ok(floor)
+        } else {
+            floor.checked_add(1).ok_or(error!(DexError::ArithmeticOverflow))
+        }
+    }
+
+    // `amount / price`, rounded down
+    pub fn div_u64(&self, amount: u64) -> Result<u64> {
+        let result = ((amount as u128) << FRAC_BITS)
+            .checked_div(self.value)
+            .ok_or(error!(DexError::ArithmeticOverflow))?;
+        u64::try_from(result).map_err(|_| error!(DexError::ArithmeticOverflow))
+    }
+
+    // `amount / price`, rounded up
+    pub fn div_u64_ceil(&self, amount: u64) -> Result<u64> {
+        if self.value == 0 {
+            return Err(error!(DexError::ArithmeticOverflow));
+        }
+        let scaled = (amount as u128) << FRAC_BITS;
+        let result = scaled / self.value + u128::from(scaled % self.value != 0);
+        u64::try_from(result).map_err(|_| error!(DexError::ArithmeticOverflow))
+    }
+
+    // `amount * numerator / denominator` for two prices, rounded down
+    pub fn mul_ratio_u64(amount: u64, numerator: &FixedPrice, denominator: &FixedPrice) -> Result<u64> {
+        let (product, den) = Self::ratio_parts(amount, numerator, denominator)?;
+        u64::try_from(product / den).map_err(|_| error!(DexError::ArithmeticOverflow))
+    }
+
+    // `amount * numerator / denominator` for two prices, rounded up
+    pub fn mul_ratio_u64_ceil(amount: u64, numerator: &FixedPrice, denominator: &FixedPrice) -> Result<u64> {
+        let (product, den) = Self::ratio_parts(amount, numerator, denominator)?;
+        let result = product / den + u128::from(product % den != 0);
+        u64::try_from(result).map_err(|_| error!(DexError::ArithmeticOverflow))
+    }
+
+    fn ratio_parts(amount: u64, numerator: &FixedPrice, denominator: &FixedPrice) -> Result<(u128, u128)> {
+        // Drop low bits of both prices so the numerator fits in 64 bits; the ratio is preserved
+        let shift = (128 - numerator.value.max(denominator.value).leading_zeros()).saturating_sub(64);
+        let num = numerator.value >> shift;
+        let den = denominator.value >> shift;
+        if den == 0 {
+            return Err(error!(DexError::ArithmeticOverflow));
+        }
+        Ok(((amount as u128) * num, den))
+    }
+
+    // Convert a price in whole tokens into atoms: `price * 10^quote_decimals / 10^base_decimals`
+    pub fn scale_decimals(&self, base_decimals: u8, quote_decimals: u8) -> Result<Self> {
+        let exponent = (quote_decimals as i32 - base_decimals as i32).unsigned_abs();
+        let scale = 10u128
+            .checked_pow(exponent)
+            .ok_or(error!(DexError::ArithmeticOverflow))?;
+        let value = if quote_decimals >= base_decimals {
+            self.value
+                .checked_mul(scale)
+                .ok_or(error!(DexError::ArithmeticOverflow))?
+        } else {
+            self.value / scale
+        };
+        Ok(FixedPrice { value })
+    }
+
+    // Scale the price by `bps / 10_000`
+    pub fn mul_bps(&self, bps: u64) -> Result<Self> {
+        let value = self.value
+            .checked_mul(bps as u128)
+            .ok_or(error!(DexError::ArithmeticOverflow))?
+            / BPS_DENOMINATOR as u128;
+        Ok(FixedPrice { value })
+    }
+
+    pub fn checked_add(&self, other: FixedPrice) -> Result<Self> {
+        let value = self.value
+            .checked_add(other.value)
+            .ok_or(error!(DexError::ArithmeticOverflow))?;
+        Ok(FixedPrice { value })
+    }
+
+    pub fn checked_sub(&self, other: FixedPrice) -> Result<Self> {
+        let value = self.value
+            .checked_sub(other.value)
+            .ok_or(error!(DexError::ArithmeticOverflow))?;
+        Ok(FixedPrice { value })
+    }
+
+    // Relative change from `self` to `new_price` in basis points
+    pub fn change_bps(&self, new_price: &FixedPrice) -> Result<u64> {
+        if self.is_zero() {
+            return Err(error!(DexError::ArithmeticOverflow));
+        }
+        let diff = self.value.abs_diff(new_price.value);
+        let bps = diff
+            .checked_mul(BPS_DENOMINATOR as u128)
+            .ok_or(error!(DexError::ArithmeticOverflow))?
+            / self.value;
+        u64::try_from(bps).map_err(|_| error!(DexError::ArithmeticOverflow))
+    }
+}
+
+impl fmt::Display for FixedPrice {
+    // Formats with 6 decimal places, e.g. "100.250000"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let int = self.value >> FRAC_BITS;
+        let frac = ((self.value & FRAC_MASK) * 1_000_000) >> FRAC_BITS;
+        write!(f, "{}.{:06}", int, frac)
+    }
+}
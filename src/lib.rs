@@ -1,45 +1,489 @@
 #![cfg_attr(not(test), forbid(overflowing_literals))]
 
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_lang::solana_program::{bpf_loader_upgradeable, sysvar};
+use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount, Transfer};
 use ephemeral_rollups_sdk::anchor::{ephemeral};
+use ephemeral_rollups_sdk::cpi::DelegateConfig;
+use ephemeral_rollups_sdk::ephem::commit_and_undelegate_accounts;
 
 declare_id!("BuLKerDex1111111111111111111111111111111111");
 
-mod error;
-mod pool;
+pub mod error;
+pub mod events;
+pub mod fixed_point;
+pub mod config;
+pub mod curve;
+pub mod fees;
+pub mod pool;
 mod delegation;
-mod oracle;
-mod pyth_parser;
-mod util;
+pub mod oracle;
+pub mod pyth_parser;
+pub mod settlement;
 
 use crate::{
+    config::{
+        check_upgrade_authority, validate_pause_flags, Config, ConfigParams, PAUSE_DELEGATION, PAUSE_DEPOSIT,
+        PAUSE_WITHDRAW,
+    },
     error::DexError,
-    pool::{Pool, TokenType},
+    events::{FeesClaimed, LiquidityDeposited, LiquidityWithdrawn, PoolDelegated, SwapExecuted},
+    pool::{InitializePoolParams, Pool, SwapDirection, SwapExactOutParams, SwapParams},
     oracle::OracleManager,
-    util::{convert_pubkey_bytes, anchor_to_program_pubkey, program_to_anchor_pubkey},
+    settlement::{LazerUpdate, SwapQuote},
 };
 
+// Accounts structs defined outside lib.rs must be reachable from the crate root
+pub use delegation::*;
+
 #[ephemeral]
 #[program]
 pub mod bulker_dex {
     use super::*;
 
-    pub fn initialize_pool(
-        ctx: Context<InitializePool>,
-        initial_sol: u64,
-        initial_usdc: u64,
-        concentration_factor: u8,
+    pub fn initialize_config(ctx: Context<InitializeConfig>, params: ConfigParams) -> Result<()> {
+        check_upgrade_authority(&ctx.accounts.program_data, &ctx.accounts.admin.key())?;
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.pending_admin = Pubkey::default();
+        config.apply_params(params)?;
+        config.bump = ctx.bumps.config;
+
+        msg!("Initialized config with admin {} and treasury {}", config.admin, config.treasury);
+        Ok(())
+    }
+
+    pub fn update_config(ctx: Context<UpdateConfig>, params: ConfigParams) -> Result<()> {
+        ctx.accounts.config.apply_params(params)?;
+
+        msg!("Updated config");
+        Ok(())
+    }
+
+    // Pyth Lazer keys whose signed prices `swap_lazer` accepts
+    pub fn set_trusted_signers(ctx: Context<UpdateConfig>, trusted_signers: Vec<Pubkey>) -> Result<()> {
+        ctx.accounts.config.set_trusted_signers(trusted_signers)?;
+
+        msg!("Trusted Lazer signers set to {:?}", ctx.accounts.config.trusted_signers);
+        Ok(())
+    }
+
+    // First step of an admin handover; takes effect once the new admin accepts
+    pub fn transfer_admin(ctx: Context<UpdateConfig>, new_admin: Pubkey) -> Result<()> {
+        ctx.accounts.config.propose_admin(new_admin);
+
+        msg!("Proposed admin transfer to {}", new_admin);
+        Ok(())
+    }
+
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let old_admin = config.admin;
+        config.accept_admin(&ctx.accounts.new_admin.key())?;
+
+        msg!("Admin transferred from {} to {}", old_admin, config.admin);
+        Ok(())
+    }
+
+    pub fn initialize_pool(ctx: Context<InitializePool>, params: InitializePoolParams) -> Result<()> {
+        ctx.accounts.process(&ctx.bumps, params)
+    }
+
+    pub fn deposit_liquidity(
+        ctx: Context<DepositLiquidity>,
+        base_amount: u64,
+        quote_amount: u64,
+        min_lp_out: u64,
     ) -> Result<()> {
+        if base_amount == 0 && quote_amount == 0 {
+            return Err(error!(DexError::InvalidDepositAmount));
+        }
+
+        let slot = Clock::get()?.slot;
         let pool = &mut ctx.accounts.pool;
-        pool.sol_reserve = initial_sol;
-        pool.usdc_reserve = initial_usdc;
-        pool.oracle_price = OracleManager::get_initial_price();
-        pool.concentration_factor = concentration_factor;
-        pool.bump = ctx.bumps.pool;
-        let auth_key = ctx.accounts.magic_block_auth.key();
-        pool.magic_block_delegate = anchor_to_program_pubkey(&auth_key);
-        
+
+        // Shares are priced at a fresh, confident oracle price; a stale one would let a
+        // deposit and withdrawal trade against the pool at yesterday's price
+        let oracle_price = settlement::refresh_oracle_price(
+            pool,
+            &ctx.accounts.oracle,
+            None,
+            &ctx.accounts.config,
+            PAUSE_DEPOSIT,
+            slot,
+        )?;
+        pool.sync_reserves(ctx.accounts.base_vault.amount, ctx.accounts.quote_vault.amount)?;
+        // The refresh only re-centers on moves past the rebalance threshold, so mark the
+        // pool to the live price even for smaller ones
+        pool.rebalance(oracle_price.price)?;
+
+        // Shares are priced against the value of the pool before this deposit
+        let lp_amount = pool.compute_lp_shares(base_amount, quote_amount, ctx.accounts.lp_mint.supply)?;
+        if lp_amount == 0 {
+            return Err(error!(DexError::InvalidDepositAmount));
+        }
+        if lp_amount < min_lp_out {
+            msg!("Slippage exceeded: got {} LP but minimum is {}", lp_amount, min_lp_out);
+            return Err(error!(DexError::SlippageExceeded));
+        }
+
+        // Transfer base in
+        if base_amount > 0 {
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.user_base.to_account_info(),
+                to: ctx.accounts.base_vault.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+            if let Err(err) = token::transfer(cpi_ctx, base_amount) {
+                msg!("Error transferring base token: {:?}", err);
+                return Err(error!(DexError::TokenTransferFailed));
+            }
+        }
+
+        // Transfer quote in
+        if quote_amount > 0 {
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.user_quote.to_account_info(),
+                to: ctx.accounts.quote_vault.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+            if let Err(err) = token::transfer(cpi_ctx, quote_amount) {
+                msg!("Error transferring quote token: {:?}", err);
+                return Err(error!(DexError::TokenTransferFailed));
+            }
+        }
+
+        // Mint LP shares, signed by the pool PDA
+        {
+            let seeds = pool.signer_seeds();
+            let signer_seeds = &[&seeds[..]];
+
+            let cpi_accounts = MintTo {
+                mint: ctx.accounts.lp_mint.to_account_info(),
+                to: ctx.accounts.user_lp.to_account_info(),
+                authority: pool.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                cpi_accounts,
+                signer_seeds,
+            );
+            token::mint_to(cpi_ctx, lp_amount)?;
+        }
+
+        pool.base_reserve = pool.base_reserve.checked_add(base_amount)
+            .ok_or(error!(DexError::ArithmeticOverflow))?;
+        pool.quote_reserve = pool.quote_reserve.checked_add(quote_amount)
+            .ok_or(error!(DexError::ArithmeticOverflow))?;
+        // Deepen the curve around the current price
+        pool.recenter()?;
+
+        emit!(LiquidityDeposited {
+            pool: pool.key(),
+            user: ctx.accounts.user.key(),
+            base_amount,
+            quote_amount,
+            lp_amount,
+            slot: Clock::get()?.slot,
+        });
+
+        msg!("Deposited {} base and {} quote for {} LP shares", base_amount, quote_amount, lp_amount);
+        Ok(())
+    }
+
+    pub fn withdraw_liquidity(
+        ctx: Context<WithdrawLiquidity>,
+        lp_amount: u64,
+        min_base_out: u64,
+        min_quote_out: u64,
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        pool.check_not_paused(ctx.accounts.config.paused, PAUSE_WITHDRAW)?;
+
+        // Committed state would diverge from the rollup copy
+        if pool.delegated {
+            msg!("Withdrawals are disabled while the pool is delegated");
+            return Err(error!(DexError::PoolDelegated));
+        }
+        if lp_amount == 0 {
+            return Err(error!(DexError::InvalidWithdrawAmount));
+        }
+
+        // Redeemed from the vault balances less protocol fees, the same value deposits are
+        // priced against
+        pool.sync_reserves(ctx.accounts.base_vault.amount, ctx.accounts.quote_vault.amount)?;
+        let (base_out, quote_out) = pool.compute_withdrawal(lp_amount, ctx.accounts.lp_mint.supply)?;
+
+        // Check slippage
+        if base_out < min_base_out || quote_out < min_quote_out {
+            msg!("Slippage exceeded: got {} base / {} quote but minimum is {} base / {} quote",
+                base_out, quote_out, min_base_out, min_quote_out);
+            return Err(error!(DexError::SlippageExceeded));
+        }
+
+        // Burn LP shares
+        {
+            let cpi_accounts = Burn {
+                mint: ctx.accounts.lp_mint.to_account_info(),
+                from: ctx.accounts.user_lp.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+            token::burn(cpi_ctx, lp_amount)?;
+        }
+
+        // Transfer base and quote out, signed by the pool PDA
+        {
+            let seeds = pool.signer_seeds();
+            let signer_seeds = &[&seeds[..]];
+
+            if base_out > 0 {
+                let cpi_accounts = Transfer {
+                    from: ctx.accounts.base_vault.to_account_info(),
+                    to: ctx.accounts.user_base.to_account_info(),
+                    authority: pool.to_account_info(),
+                };
+                let cpi_ctx = CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    cpi_accounts,
+                    signer_seeds,
+                );
+                if let Err(err) = token::transfer(cpi_ctx, base_out) {
+                    msg!("Error transferring base token: {:?}", err);
+                    return Err(error!(DexError::TokenTransferFailed));
+                }
+            }
+
+            if quote_out > 0 {
+                let cpi_accounts = Transfer {
+                    from: ctx.accounts.quote_vault.to_account_info(),
+                    to: ctx.accounts.user_quote.to_account_info(),
+                    authority: pool.to_account_info(),
+                };
+                let cpi_ctx = CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    cpi_accounts,
+                    signer_seeds,
+                );
+                if let Err(err) = token::transfer(cpi_ctx, quote_out) {
+                    msg!("Error transferring quote token: {:?}", err);
+                    return Err(error!(DexError::TokenTransferFailed));
+                }
+            }
+        }
+
+        pool.base_reserve = pool.base_reserve.checked_sub(base_out)
+            .ok_or(error!(DexError::InsufficientLiquidity))?;
+        pool.quote_reserve = pool.quote_reserve.checked_sub(quote_out)
+            .ok_or(error!(DexError::InsufficientLiquidity))?;
+        // Shrink the curve with the pool
+        pool.recenter()?;
+
+        emit!(LiquidityWithdrawn {
+            pool: pool.key(),
+            user: ctx.accounts.user.key(),
+            base_amount: base_out,
+            quote_amount: quote_out,
+            lp_amount,
+            slot: Clock::get()?.slot,
+        });
+
+        msg!("Withdrew {} base and {} quote for {} LP shares", base_out, quote_out, lp_amount);
+        Ok(())
+    }
+
+    pub fn update_fee(ctx: Context<UpdatePoolSettings>, fee_bps: u16) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        let old_fee_bps = pool.fee_bps;
+        pool.set_fee_bps(fee_bps)?;
+
+        msg!("Updated pool fee from {} bps to {} bps", old_fee_bps, fee_bps);
+        Ok(())
+    }
+
+    pub fn set_fee_bounds(
+        ctx: Context<UpdatePoolSettings>,
+        min_fee_bps: u16,
+        max_fee_bps: u16,
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        pool.set_fee_bounds(min_fee_bps, max_fee_bps)?;
+
+        msg!("Dynamic fee bounds set to {}-{} bps", min_fee_bps, max_fee_bps);
+        Ok(())
+    }
+
+    // The pool authority tunes the breaker, but a trip pauses swaps until the guardian
+    // resumes them through set_pool_paused: pausing is the protocol's safety switch.
+    // Swaps and deposits fail on a trip; trip_circuit_breaker persists the pause.
+    pub fn set_circuit_breaker(
+        ctx: Context<UpdatePoolSettings>,
+        threshold_bps: u16,
+        window_slots: u64,
+    ) -> Result<()> {
+        ctx.accounts.pool.set_circuit_breaker(threshold_bps, window_slots);
+
+        msg!("Circuit breaker set to {} bps within {} slots", threshold_bps, window_slots);
+        Ok(())
+    }
+
+    // Pyth Lazer feed `swap_lazer` prices against; 0 disables it
+    pub fn set_lazer_feed(ctx: Context<UpdatePoolSettings>, feed_id: u32) -> Result<()> {
+        ctx.accounts.pool.lazer_feed_id = feed_id;
+
+        msg!("Lazer feed set to {}", feed_id);
+        Ok(())
+    }
+
+    pub fn set_rebalance_params(
+        ctx: Context<UpdatePoolSettings>,
+        target_base_bps: u16,
+        rebalance_cap_bps: u16,
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        pool.set_rebalance_params(target_base_bps, rebalance_cap_bps)?;
+        let curve_price = pool.recenter()?;
+
+        msg!("Rebalance target set to {} bps base with a {} bps cap, curve price {}",
+            target_base_bps, rebalance_cap_bps, curve_price);
+        Ok(())
+    }
+
+    // Anyone may call this. Swaps and deposits fail when the oracle moves past the breaker
+    // threshold, and a failed transaction cannot keep the pause, so keepers persist it here.
+    pub fn trip_circuit_breaker(ctx: Context<TripCircuitBreaker>) -> Result<()> {
+        let slot = Clock::get()?.slot;
+        let accounts = ctx.accounts;
+        let oracle_price = settlement::read_oracle_price(&mut accounts.pool, &accounts.oracle, None, &accounts.config)?;
+        if !settlement::check_circuit_breaker(&mut accounts.pool, oracle_price.price, slot)? {
+            msg!("Oracle price {} is within the circuit breaker threshold", oracle_price.price);
+            return Err(error!(DexError::CircuitBreakerNotTripped));
+        }
+
+        msg!("Circuit breaker tripped at {} - swaps paused", oracle_price.price);
+        Ok(())
+    }
+
+    // Guardian only, including clearing a tripped circuit breaker
+    pub fn set_pool_paused(ctx: Context<SetPoolPaused>, paused: u8) -> Result<()> {
+        ctx.accounts.pool.set_paused(paused)?;
+
+        msg!("Pool pause flags set to {:#06b}", paused);
+        Ok(())
+    }
+
+    pub fn set_global_paused(ctx: Context<SetGlobalPaused>, paused: u8) -> Result<()> {
+        validate_pause_flags(paused)?;
+        ctx.accounts.config.paused = paused;
+
+        msg!("Global pause flags set to {:#06b}", paused);
+        Ok(())
+    }
+
+    pub fn claim_protocol_fees(ctx: Context<ClaimProtocolFees>) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+
+        // Counters on the rollup copy may be ahead of the base layer
+        if pool.delegated {
+            msg!("Protocol fees cannot be claimed while the pool is delegated");
+            return Err(error!(DexError::PoolDelegated));
+        }
+
+        // Protocol fees were never part of the reserves, so only the counters change
+        let (base_amount, quote_amount) = pool.take_protocol_fees();
+
+        // Transfer accrued fees to the treasury, signed by the pool PDA
+        {
+            let seeds = pool.signer_seeds();
+            let signer_seeds = &[&seeds[..]];
+
+            if base_amount > 0 {
+                let cpi_accounts = Transfer {
+                    from: ctx.accounts.base_vault.to_account_info(),
+                    to: ctx.accounts.treasury_base.to_account_info(),
+                    authority: pool.to_account_info(),
+                };
+                let cpi_ctx = CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    cpi_accounts,
+                    signer_seeds,
+                );
+                if let Err(err) = token::transfer(cpi_ctx, base_amount) {
+                    msg!("Error transferring base token: {:?}", err);
+                    return Err(error!(DexError::TokenTransferFailed));
+                }
+            }
+
+            if quote_amount > 0 {
+                let cpi_accounts = Transfer {
+                    from: ctx.accounts.quote_vault.to_account_info(),
+                    to: ctx.accounts.treasury_quote.to_account_info(),
+                    authority: pool.to_account_info(),
+                };
+                let cpi_ctx = CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    cpi_accounts,
+                    signer_seeds,
+                );
+                if let Err(err) = token::transfer(cpi_ctx, quote_amount) {
+                    msg!("Error transferring quote token: {:?}", err);
+                    return Err(error!(DexError::TokenTransferFailed));
+                }
+            }
+        }
+
+        emit!(FeesClaimed {
+            pool: pool.key(),
+            treasury: ctx.accounts.config.treasury,
+            base_amount,
+            quote_amount,
+            slot: Clock::get()?.slot,
+        });
+
+        msg!("Claimed {} base and {} quote protocol fees", base_amount, quote_amount);
+        Ok(())
+    }
+
+    pub fn delegate_pool(ctx: Context<DelegateSwap>) -> Result<()> {
+        // Record the delegation while the program still owns the account
+        let pool = delegation::set_pool_delegated(&ctx.accounts.pool, true)?;
+        if pool.authority != ctx.accounts.payer.key() {
+            return Err(error!(DexError::Unauthorized));
+        }
+        pool.check_not_paused(ctx.accounts.config.paused, PAUSE_DELEGATION)?;
+
+        let seeds = pool.signer_seeds();
+        ctx.accounts.delegate_pool(
+            &ctx.accounts.payer,
+            &seeds[..4],
+            DelegateConfig::default(),
+        )?;
+
+        emit!(PoolDelegated {
+            pool: ctx.accounts.pool.key(),
+            authority: pool.authority,
+            slot: Clock::get()?.slot,
+        });
+
+        msg!("Delegated pool {} to Magic Block", ctx.accounts.pool.key());
+        Ok(())
+    }
+
+    pub fn undelegate_pool(ctx: Context<UndelegatePool>) -> Result<()> {
+        ctx.accounts.pool.delegated = false;
+        // Persist the flag before the commit snapshot is taken
+        ctx.accounts.pool.exit(&crate::ID)?;
+
+        commit_and_undelegate_accounts(
+            &ctx.accounts.payer,
+            vec![&ctx.accounts.pool.to_account_info()],
+            &ctx.accounts.magic_context,
+            &ctx.accounts.magic_program,
+        )?;
+
+        msg!("Committed and undelegated pool {}", ctx.accounts.pool.key());
         Ok(())
     }
 
@@ -47,30 +491,50 @@ pub mod bulker_dex {
         ctx: Context<Swap>,
         amount_in: u64,
         min_amount_out: u64,
-        input_token: TokenType,
+        direction: SwapDirection,
+        max_price_impact_bps: u16,
     ) -> Result<()> {
-        let _pool = &mut ctx.accounts.pool;
-        
-        // Validate oracle account
-        OracleManager::validate_oracle_account(&ctx.accounts.oracle)?;
-        
-        // Serialize the data to delegate
-        let delegated_swap = delegation::DelegatedSwap {
-            input_token,
+        ctx.accounts.process(SwapParams {
+            direction,
             amount_in,
             min_amount_out,
+            max_price_impact_bps,
+        })
+    }
+
+    // Exact-input swap priced from a signed Pyth Lazer message. The transaction must carry an
+    // Ed25519 program instruction at `ed25519_ix_index` verifying `message`'s signature.
+    pub fn swap_lazer(
+        ctx: Context<SwapLazer>,
+        amount_in: u64,
+        min_amount_out: u64,
+        direction: SwapDirection,
+        max_price_impact_bps: u16,
+        message: Vec<u8>,
+        ed25519_ix_index: u16,
+    ) -> Result<()> {
+        let params = SwapParams {
+            direction,
+            amount_in,
+            min_amount_out,
+            max_price_impact_bps,
         };
-        
-        let serialized_data = delegated_swap.try_to_vec()?;
-        
-        // Delegate the transaction to Magic Block
-        msg!("Delegating swap to Magic Block for {} token", 
-            if matches!(input_token, TokenType::SOL) { "SOL" } else { "USDC" });
-        
-        // Set the return data for delegation
-        anchor_lang::solana_program::program::set_return_data(&serialized_data);
+        ctx.accounts.process(params, &message, ed25519_ix_index)
+    }
 
-        Ok(())
+    pub fn swap_exact_out(
+        ctx: Context<Swap>,
+        amount_out: u64,
+        max_amount_in: u64,
+        direction: SwapDirection,
+        max_price_impact_bps: u16,
+    ) -> Result<()> {
+        ctx.accounts.process_exact_out(SwapExactOutParams {
+            direction,
+            amount_out,
+            max_amount_in,
+            max_price_impact_bps,
+        })
     }
     
     // Add a new function to handle delegated swaps from Magic Block
@@ -82,188 +546,530 @@ pub mod bulker_dex {
         // Validate Magic Block authority
         delegation::validate_magic_block_authority(
             &ctx.accounts.magic_block_authority.key(),
-            &ctx.accounts.pool.magic_block_delegate
+            &ctx.accounts.config.magic_block_authority
         )?;
         
         // Decode the delegated swap data
-        let delegated_swap = delegation::decode_delegated_swap(&data)?;
-        
-        let input_token = delegated_swap.input_token;
-        let amount_in = delegated_swap.amount_in;
-        let min_amount_out = delegated_swap.min_amount_out;
-        
-        msg!("Processing delegated swap: {:?} token, {} in, {} min out", 
-            input_token, amount_in, min_amount_out);
-        
-        let pool = &mut ctx.accounts.pool;
-        
-        // Validate oracle account
-        OracleManager::validate_oracle_account(&ctx.accounts.oracle)?;
+        let params = delegation::decode_delegated_swap(&data)?;
         
-        // Get price from Pyth Lazer
-        let new_price = OracleManager::fetch_price(&ctx.accounts.oracle)?;
-        msg!("Current oracle price: {}, Pool price: {}", new_price, pool.oracle_price);
-        
-        // Rebalance if price changes > 1%
-        let price_change_threshold = 0.01;
-        let price_diff = (new_price - pool.oracle_price).abs();
-        let relative_change = price_diff / pool.oracle_price;
-        
-        if relative_change > price_change_threshold {
-            msg!("Price changed by {:.2}% - rebalancing pool", relative_change * 100.0);
-            pool.rebalance(new_price);
-        } else {
-            msg!("Price change ({:.2}%) below threshold - no rebalance needed", 
-                relative_change * 100.0);
-        }
-        
-        // Calculate swap output with concentrated liquidity
-        let (amount_out, fee) = pool.compute_swap_output(input_token, amount_in)?;
-        msg!("Computed output: {} with fee: {}", amount_out, fee);
-        
-        // Check slippage
-        if amount_out < min_amount_out {
-            msg!("Slippage exceeded: got {} but minimum is {}", 
-                amount_out, min_amount_out);
-            return Err(error!(DexError::SlippageExceeded));
-        }
-        
-        // Update reserves
-        match input_token {
-            TokenType::SOL => {
-                pool.sol_reserve = pool.sol_reserve.checked_add(amount_in)
-                    .ok_or(error!(DexError::ArithmeticOverflow))?;
-                pool.usdc_reserve = pool.usdc_reserve.checked_sub(amount_out)
-                    .ok_or(error!(DexError::ArithmeticOverflow))?;
-                
-                // Transfer USDC out
-                {
-                    // Create longer-lived values for the seeds
-                    let authority_key = ctx.accounts.authority.key();
-                    let authority_ref = authority_key.as_ref();
-                    let bump = pool.bump;
-                    let bump_ref = &[bump];
-                    let seeds = &[b"pool" as &[u8], authority_ref, bump_ref][..];
-                    let signer_seeds = &[&seeds[..]];
-                    
-                    let cpi_accounts = Transfer {
-                        from: ctx.accounts.pool_usdc.to_account_info(),
-                        to: ctx.accounts.user_usdc.to_account_info(),
-                        authority: ctx.accounts.authority.to_account_info(),
-                    };
-                    
-                    let cpi_ctx = CpiContext::new_with_signer(
-                        ctx.accounts.token_program.to_account_info(),
-                        cpi_accounts,
-                        signer_seeds,
-                    );
-                    if let Err(err) = token::transfer(cpi_ctx, amount_out) {
-                        msg!("Error transferring USDC: {:?}", err);
-                        return Err(error!(DexError::TokenTransferFailed));
-                    }
-                }
-            }
-            TokenType::USDC => {
-                pool.usdc_reserve = pool.usdc_reserve.checked_add(amount_in)
-                    .ok_or(error!(DexError::ArithmeticOverflow))?;
-                pool.sol_reserve = pool.sol_reserve.checked_sub(amount_out)
-                    .ok_or(error!(DexError::ArithmeticOverflow))?;
-                
-                // Transfer SOL out
-                {
-                    // Create longer-lived values for the seeds
-                    let authority_key = ctx.accounts.authority.key();
-                    let authority_ref = authority_key.as_ref();
-                    let bump = pool.bump;
-                    let bump_ref = &[bump];
-                    let seeds = &[b"pool" as &[u8], authority_ref, bump_ref][..];
-                    let signer_seeds = &[&seeds[..]];
-                    
-                    let cpi_accounts = Transfer {
-                        from: ctx.accounts.pool_sol.to_account_info(),
-                        to: ctx.accounts.user_sol.to_account_info(),
-                        authority: ctx.accounts.authority.to_account_info(),
-                    };
-                    
-                    let cpi_ctx = CpiContext::new_with_signer(
-                        ctx.accounts.token_program.to_account_info(),
-                        cpi_accounts,
-                        signer_seeds,
-                    );
-                    
-                    if let Err(err) = token::transfer(cpi_ctx, amount_out) {
-                        msg!("Error transferring SOL: {:?}", err);
-                        return Err(error!(DexError::TokenTransferFailed));
-                    }
-                }
-            }
-        }
+        msg!("Processing delegated swap: {:?}, {} in, {} min out, {} bps max impact", 
+            params.direction, params.amount_in, params.min_amount_out, params.max_price_impact_bps);
         
-        msg!("Successfully processed delegated swap via Magic Block: {} {} -> {} {}", 
-            amount_in, 
-            if matches!(input_token, TokenType::SOL) { "SOL" } else { "USDC" },
-            amount_out,
-            if matches!(input_token, TokenType::SOL) { "USDC" } else { "SOL" });
-        Ok(())
+        ctx.accounts.process(params)
     }
 }
 
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Config::LEN,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    // This program's own ProgramData, which records its upgrade authority
+    #[account(seeds = [crate::ID.as_ref()], bump, seeds::program = bpf_loader_upgradeable::ID)]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(mut, seeds = [b"config"], bump = config.bump, has_one = admin @ DexError::Unauthorized)]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.pending_admin == new_admin.key() @ DexError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+    pub new_admin: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct InitializePool<'info> {
     #[account(
         init,
         payer = authority,
         space = 8 + Pool::LEN,
-        seeds = [b"pool", authority.key().as_ref()],
+        seeds = [b"pool", authority.key().as_ref(), base_mint.key().as_ref(), quote_mint.key().as_ref()],
         bump
     )]
     pub pool: Account<'info, Pool>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"lp_mint", pool.key().as_ref()],
+        bump,
+        mint::decimals = quote_mint.decimals,
+        mint::authority = pool,
+    )]
+    pub lp_mint: Account<'info, Mint>,
+    pub base_mint: Account<'info, Mint>,
+    #[account(constraint = quote_mint.key() != base_mint.key() @ DexError::InvalidMint)]
+    pub quote_mint: Account<'info, Mint>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"vault", pool.key().as_ref(), base_mint.key().as_ref()],
+        bump,
+        token::mint = base_mint,
+        token::authority = pool,
+    )]
+    pub base_vault: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"vault", pool.key().as_ref(), quote_mint.key().as_ref()],
+        bump,
+        token::mint = quote_mint,
+        token::authority = pool,
+    )]
+    pub quote_vault: Account<'info, TokenAccount>,
+    /// CHECK: Price feed for the pair, validated when the initial price is read
+    pub oracle: UncheckedAccount<'info>,
     #[account(mut)]
     pub authority: Signer<'info>,
-    /// CHECK: This is the Magic Block authority
-    pub magic_block_auth: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.restrict_pool_creation || config.admin == authority.key() @ DexError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
+impl<'info> InitializePool<'info> {
+    pub fn process(&mut self, bumps: &InitializePoolBumps, params: InitializePoolParams) -> Result<()> {
+        let config = &self.config;
+        let pool = &mut self.pool;
+        pool.set_fee_bps(params.fee_bps.unwrap_or(config.default_fee_bps))?;
+        pool.set_rebalance_params(params.target_base_bps, params.rebalance_cap_bps)?;
+        pool.set_fee_bounds(params.min_fee_bps, params.max_fee_bps)?;
+        pool.set_curve(params.curve_type, params.base_spread_bps)?;
+        pool.base_mint = self.base_mint.key();
+        pool.quote_mint = self.quote_mint.key();
+        pool.base_vault = self.base_vault.key();
+        pool.quote_vault = self.quote_vault.key();
+        pool.base_decimals = self.base_mint.decimals;
+        pool.quote_decimals = self.quote_mint.decimals;
+        pool.oracle = self.oracle.key();
+        // Vaults start empty; liquidity is added through deposit_liquidity
+        pool.base_reserve = 0;
+        pool.quote_reserve = 0;
+        pool.set_concentration_factor(params.concentration_factor)?;
+        pool.max_confidence_bps = params.max_confidence_bps;
+        pool.confidence_multiplier_bps = params.confidence_multiplier_bps;
+        pool.max_oracle_age_slots = params.max_oracle_age_slots;
+        pool.max_oracle_age_secs = params.max_oracle_age_secs;
+
+        // Seed the pool price from its own feed
+        let oracle_price = OracleManager::fetch_price(
+            &self.oracle,
+            &config.oracle_programs,
+            params.max_oracle_age_slots,
+            params.max_oracle_age_secs,
+        )?;
+        pool.oracle_price = oracle_price.price.scale_decimals(pool.base_decimals, pool.quote_decimals)?;
+        pool.bump = bumps.pool;
+        pool.authority = self.authority.key();
+        pool.lp_mint = self.lp_mint.key();
+
+        Ok(())
+    }
+}
+
 #[derive(Accounts)]
-pub struct Swap<'info> {
+pub struct SetGlobalPaused<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = guardian @ DexError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+    pub guardian: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPoolPaused<'info> {
+    #[account(seeds = [b"config"], bump = config.bump, has_one = guardian @ DexError::Unauthorized)]
+    pub config: Account<'info, Config>,
+    pub guardian: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"pool", pool.authority.as_ref(), pool.base_mint.as_ref(), pool.quote_mint.as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
+}
+
+#[derive(Accounts)]
+pub struct TripCircuitBreaker<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.authority.as_ref(), pool.base_mint.as_ref(), pool.quote_mint.as_ref()],
+        bump = pool.bump,
+        has_one = oracle @ DexError::InvalidOracleAccount,
+    )]
+    pub pool: Account<'info, Pool>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    /// CHECK: Must be the pool's feed; parsed by the oracle manager
+    pub oracle: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePoolSettings<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.authority.as_ref(), pool.base_mint.as_ref(), pool.quote_mint.as_ref()],
+        bump = pool.bump,
+        has_one = authority @ DexError::Unauthorized,
+    )]
+    pub pool: Account<'info, Pool>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimProtocolFees<'info> {
+    #[account(seeds = [b"config"], bump = config.bump, has_one = admin @ DexError::Unauthorized)]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"pool", pool.authority.as_ref(), pool.base_mint.as_ref(), pool.quote_mint.as_ref()],
+        bump = pool.bump,
+        has_one = base_vault,
+        has_one = quote_vault,
+    )]
+    pub pool: Account<'info, Pool>,
+    #[account(mut)]
+    pub base_vault: Account<'info, TokenAccount>,
     #[account(mut)]
+    pub quote_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = treasury_base.mint == pool.base_mint @ DexError::InvalidMint,
+        constraint = treasury_base.owner == config.treasury @ DexError::Unauthorized,
+    )]
+    pub treasury_base: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = treasury_quote.mint == pool.quote_mint @ DexError::InvalidMint,
+        constraint = treasury_quote.owner == config.treasury @ DexError::Unauthorized,
+    )]
+    pub treasury_quote: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct DepositLiquidity<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.authority.as_ref(), pool.base_mint.as_ref(), pool.quote_mint.as_ref()],
+        bump = pool.bump,
+        has_one = lp_mint,
+        has_one = base_vault,
+        has_one = quote_vault,
+        has_one = oracle @ DexError::InvalidOracleAccount,
+    )]
     pub pool: Account<'info, Pool>,
-    /// CHECK: This is the oracle account
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    /// CHECK: Must be the pool's feed; parsed by the oracle manager
     pub oracle: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub lp_mint: Account<'info, Mint>,
     pub user: Signer<'info>,
-    /// CHECK: This is the pool authority (used for PDA signing)
-    pub authority: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        token::authority = user,
+        constraint = user_base.mint == pool.base_mint @ DexError::InvalidMint,
+    )]
+    pub user_base: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::authority = user,
+        constraint = user_quote.mint == pool.quote_mint @ DexError::InvalidMint,
+    )]
+    pub user_quote: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = lp_mint)]
+    pub user_lp: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub base_vault: Account<'info, TokenAccount>,
     #[account(mut)]
-    pub user_sol: Account<'info, TokenAccount>,
+    pub quote_vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawLiquidity<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.authority.as_ref(), pool.base_mint.as_ref(), pool.quote_mint.as_ref()],
+        bump = pool.bump,
+        has_one = lp_mint,
+        has_one = base_vault,
+        has_one = quote_vault,
+    )]
+    pub pool: Account<'info, Pool>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
     #[account(mut)]
-    pub user_usdc: Account<'info, TokenAccount>,
+    pub lp_mint: Account<'info, Mint>,
+    pub user: Signer<'info>,
+    #[account(mut, token::mint = lp_mint, token::authority = user)]
+    pub user_lp: Account<'info, TokenAccount>,
+    #[account(mut, constraint = user_base.mint == pool.base_mint @ DexError::InvalidMint)]
+    pub user_base: Account<'info, TokenAccount>,
+    #[account(mut, constraint = user_quote.mint == pool.quote_mint @ DexError::InvalidMint)]
+    pub user_quote: Account<'info, TokenAccount>,
     #[account(mut)]
-    pub pool_sol: Account<'info, TokenAccount>,
+    pub base_vault: Account<'info, TokenAccount>,
     #[account(mut)]
-    pub pool_usdc: Account<'info, TokenAccount>,
+    pub quote_vault: Account<'info, TokenAccount>,
     pub token_program: Program<'info, Token>,
 }
 
+#[derive(Accounts)]
+pub struct Swap<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.authority.as_ref(), pool.base_mint.as_ref(), pool.quote_mint.as_ref()],
+        bump = pool.bump,
+        has_one = base_vault,
+        has_one = quote_vault,
+        has_one = oracle @ DexError::InvalidOracleAccount,
+    )]
+    pub pool: Account<'info, Pool>,
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    /// CHECK: Must be the pool's feed; parsed by the oracle manager
+    pub oracle: UncheckedAccount<'info>,
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        token::authority = user,
+        constraint = user_base.mint == pool.base_mint @ DexError::InvalidMint,
+    )]
+    pub user_base: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::authority = user,
+        constraint = user_quote.mint == pool.quote_mint @ DexError::InvalidMint,
+    )]
+    pub user_quote: Account<'info, TokenAccount>,
+    #[account(mut, token::authority = pool)]
+    pub base_vault: Account<'info, TokenAccount>,
+    #[account(mut, token::authority = pool)]
+    pub quote_vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SwapLazer<'info> {
+    pub swap: Swap<'info>,
+    /// CHECK: Instructions sysvar, read for the Ed25519 signature check
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+}
+
+impl<'info> SwapLazer<'info> {
+    pub fn process(&mut self, params: SwapParams, message: &[u8], ed25519_ix_index: u16) -> Result<()> {
+        let slot = Clock::get()?.slot;
+        let instructions_sysvar = self.instructions_sysvar.to_account_info();
+        let lazer = LazerUpdate { instructions_sysvar: &instructions_sysvar, ed25519_ix_index, message };
+        let swap = &mut self.swap;
+        let quote = settlement::quote_swap(&mut swap.pool, &swap.oracle, Some(&lazer), &swap.config, &params, slot)?;
+        swap.settle(params.direction, quote, slot)
+    }
+}
+
+impl<'info> Swap<'info> {
+    pub fn process(&mut self, params: SwapParams) -> Result<()> {
+        let slot = Clock::get()?.slot;
+        let quote = settlement::quote_swap(&mut self.pool, &self.oracle, None, &self.config, &params, slot)?;
+        self.settle(params.direction, quote, slot)
+    }
+
+    pub fn process_exact_out(&mut self, params: SwapExactOutParams) -> Result<()> {
+        let slot = Clock::get()?.slot;
+        let quote = settlement::quote_swap_exact_out(&mut self.pool, &self.oracle, &self.config, &params, slot)?;
+        self.settle(params.direction, quote, slot)
+    }
+
+    fn settle(&mut self, direction: SwapDirection, quote: SwapQuote, slot: u64) -> Result<()> {
+        let SwapQuote { amount_in, amount_out, fee, price } = quote;
+        let pool = &mut self.pool;
+
+        // Update reserves; the fee stays in the pool for LPs
+        pool.update_reserves(direction, amount_in, amount_out, fee, self.config.protocol_fee_bps)?;
+
+        // Collect the input from the user, then pay out from the other vault
+        let (user_in, vault_in, vault_out, user_out) = match direction {
+            SwapDirection::BaseToQuote => (
+                &self.user_base,
+                &self.base_vault,
+                &self.quote_vault,
+                &self.user_quote,
+            ),
+            SwapDirection::QuoteToBase => (
+                &self.user_quote,
+                &self.quote_vault,
+                &self.base_vault,
+                &self.user_base,
+            ),
+        };
+        settlement::transfer_to_vault(
+            &self.token_program,
+            user_in,
+            vault_in,
+            &self.user,
+            amount_in,
+        )?;
+        settlement::transfer_from_vault(
+            &self.token_program,
+            vault_out,
+            user_out,
+            pool,
+            amount_out,
+        )?;
+
+        self.base_vault.reload()?;
+        self.quote_vault.reload()?;
+        pool.check_vault_invariant(self.base_vault.amount, self.quote_vault.amount)?;
+
+        emit!(SwapExecuted {
+            pool: pool.key(),
+            user: self.user.key(),
+            direction,
+            amount_in,
+            amount_out,
+            fee,
+            oracle_price: price,
+            slot,
+        });
+
+        msg!("Swapped {:?}: {} in -> {} out", direction, amount_in, amount_out);
+        Ok(())
+    }
+}
+
 // Add account validation for delegated swaps
 #[derive(Accounts)]
 pub struct DelegatedSwap<'info> {
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"pool", pool.authority.as_ref(), pool.base_mint.as_ref(), pool.quote_mint.as_ref()],
+        bump = pool.bump,
+        has_one = base_vault,
+        has_one = quote_vault,
+        has_one = oracle @ DexError::InvalidOracleAccount,
+    )]
     pub pool: Account<'info, Pool>,
-    /// CHECK: This is the oracle account
+    /// CHECK: Must be the pool's feed; parsed by the oracle manager
     pub oracle: UncheckedAccount<'info>,
-    /// CHECK: This is the Magic Block authority
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(
+        constraint = magic_block_authority.key() == config.magic_block_authority
+            @ DexError::InvalidMagicBlockAuthority,
+    )]
     pub magic_block_authority: Signer<'info>,
-    pub user: Signer<'info>,
-    /// CHECK: This is the pool authority (used for PDA signing)
-    pub authority: UncheckedAccount<'info>,
-    #[account(mut)]
-    pub user_sol: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub user_usdc: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub pool_sol: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub pool_usdc: Account<'info, TokenAccount>,
+    /// CHECK: Trader; either signs, or has approved the pool as delegate on the input account
+    pub user: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        token::authority = user,
+        constraint = user_base.mint == pool.base_mint @ DexError::InvalidMint,
+    )]
+    pub user_base: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::authority = user,
+        constraint = user_quote.mint == pool.quote_mint @ DexError::InvalidMint,
+    )]
+    pub user_quote: Account<'info, TokenAccount>,
+    #[account(mut, token::authority = pool)]
+    pub base_vault: Account<'info, TokenAccount>,
+    #[account(mut, token::authority = pool)]
+    pub quote_vault: Account<'info, TokenAccount>,
     pub token_program: Program<'info, Token>,
-} 
\ No newline at end of file
+}
+
+impl<'info> DelegatedSwap<'info> {
+    // Settle a swap decoded from Magic Block data, once the authority has been checked
+    pub fn process(&mut self, params: SwapParams) -> Result<()> {
+        let SwapParams { direction, amount_in, .. } = params;
+        let slot = Clock::get()?.slot;
+        let pool = &mut self.pool;
+
+        let quote = settlement::quote_swap(pool, &self.oracle, None, &self.config, &params, slot)?;
+        let SwapQuote { amount_out, fee, price: new_price, .. } = quote;
+
+        // Update reserves; the fee stays in the pool for LPs
+        pool.update_reserves(direction, amount_in, amount_out, fee, self.config.protocol_fee_bps)?;
+
+        // Collect the input, then pay out from the other vault, signed by the pool PDA
+        let (user_in, vault_in, vault_out, user_out) = match direction {
+            SwapDirection::BaseToQuote => (
+                &self.user_base,
+                &self.base_vault,
+                &self.quote_vault,
+                &self.user_quote,
+            ),
+            SwapDirection::QuoteToBase => (
+                &self.user_quote,
+                &self.quote_vault,
+                &self.base_vault,
+                &self.user_base,
+            ),
+        };
+        settlement::collect_input(
+            &self.token_program,
+            user_in,
+            vault_in,
+            &self.user,
+            pool,
+            amount_in,
+        )?;
+        settlement::transfer_from_vault(
+            &self.token_program,
+            vault_out,
+            user_out,
+            pool,
+            amount_out,
+        )?;
+
+        self.base_vault.reload()?;
+        self.quote_vault.reload()?;
+        pool.check_vault_invariant(self.base_vault.amount, self.quote_vault.amount)?;
+
+        emit!(SwapExecuted {
+            pool: pool.key(),
+            user: self.user.key(),
+            direction,
+            amount_in,
+            amount_out,
+            fee,
+            oracle_price: new_price,
+            slot,
+        });
+
+        msg!("Successfully processed delegated swap via Magic Block: {:?}, {} in -> {} out", 
+            direction, amount_in, amount_out);
+        Ok(())
+    }
+}
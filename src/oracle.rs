@@ -1,37 +1,93 @@
 use anchor_lang::prelude::*;
-use crate::{error::DexError, pyth_parser::{PYTH_LAZER_SOL_USD, get_pyth_price}};
-use std::str::FromStr;
+use crate::{
+    error::DexError,
+    fixed_point::{FixedPrice, BPS_DENOMINATOR},
+    pyth_parser::{LazerPrice, get_lazer_payload, get_pyth_price},
+};
+
+// Oracle price with its confidence interval, both in quote per base
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OraclePrice {
+    pub price: FixedPrice,
+    pub confidence: FixedPrice,
+    pub twap: Option<FixedPrice>,       // Time-weighted average, if the feed publishes one
+}
+
+impl OraclePrice {
+    // Confidence interval relative to the price, in basis points
+    pub fn confidence_bps(&self) -> Result<u64> {
+        FixedPrice::mul_ratio_u64(BPS_DENOMINATOR, &self.confidence, &self.price)
+    }
+
+    // Re-express a whole-token price in quote atoms per base atom
+    pub fn scale_decimals(&self, base_decimals: u8, quote_decimals: u8) -> Result<OraclePrice> {
+        Ok(OraclePrice {
+            price: self.price.scale_decimals(base_decimals, quote_decimals)?,
+            confidence: self.confidence.scale_decimals(base_decimals, quote_decimals)?,
+            twap: self.twap
+                .map(|twap| twap.scale_decimals(base_decimals, quote_decimals))
+                .transpose()?,
+        })
+    }
+}
 
 pub struct OracleManager;
 
 impl OracleManager {
-    pub fn fetch_price(price_account: &UncheckedAccount) -> Result<f64> {
-        // Maximum age of 20 slots for Pyth Lazer (much faster than traditional Pyth)
-        let maximum_age_in_slots: u64 = 20;
-        
+    pub fn fetch_price(
+        price_account: &UncheckedAccount,
+        oracle_programs: &[Pubkey],
+        max_age_slots: u64,
+        max_age_secs: u64,
+    ) -> Result<OraclePrice> {
         // Get price using our custom parser
-        let price = get_pyth_price(price_account, maximum_age_in_slots)
-            .map_err(|_| error!(DexError::InvalidOracleAccount))?;
+        let price_data = get_pyth_price(
+            price_account,
+            oracle_programs,
+            max_age_slots,
+            max_age_secs,
+        )?;
+        let price = price_data.get_price()?;
+        let confidence = price_data.get_confidence()?;
         
         // Log the price for easier debugging
-        msg!("Latest price from Pyth Lazer: {}", price);
+        msg!("Latest price from Pyth: {} (conf {})", price, confidence);
             
-        Ok(price)
+        Ok(OraclePrice {
+            price,
+            confidence,
+            twap: price_data.get_twap()?,
+        })
     }
 
-    pub fn get_initial_price() -> f64 {
-        // Default initial price for demonstration
-        100.0 // 1 SOL = 100 USDC
-    }
-    
-    pub fn get_pyth_feed_pubkey() -> Pubkey {
-        Pubkey::from_str(PYTH_LAZER_SOL_USD).unwrap_or_else(|_| panic!("Invalid pubkey"))
+    // Verify a signed Pyth Lazer message (checked by the Ed25519 instruction at
+    // `ed25519_ix_index`) and return the price for `feed_id`
+    pub fn fetch_lazer_price(
+        instructions_sysvar: &AccountInfo,
+        ed25519_ix_index: u16,
+        message: &[u8],
+        feed_id: u32,
+        trusted_signers: &[Pubkey],
+        max_age_secs: u64,
+    ) -> Result<LazerPrice> {
+        let payload = get_lazer_payload(instructions_sysvar, ed25519_ix_index, message, trusted_signers)?;
+        let price = payload.get_price(feed_id)?;
+
+        let clock = Clock::get()?;
+        if !price.is_fresh(&clock, max_age_secs) {
+            msg!("Lazer price is too old: published at {} us, now {}",
+                price.timestamp_us, clock.unix_timestamp);
+            return Err(error!(DexError::InvalidOracleData));
+        }
+
+        msg!("Latest price from Pyth Lazer feed {}: {} (conf {})",
+            feed_id, price.price, price.confidence);
+
+        Ok(price)
     }
-    
-    pub fn validate_oracle_account(oracle: &UncheckedAccount) -> Result<()> {
-        let expected_feed = Self::get_pyth_feed_pubkey();
-        
-        if oracle.key() != expected_feed {
+
+    pub fn validate_oracle_account(oracle: &UncheckedAccount, expected_feed: &Pubkey) -> Result<()> {
+        if oracle.key() != *expected_feed {
             msg!("Invalid oracle account: expected {}, got {}", 
                 expected_feed, oracle.key());
             return Err(error!(DexError::InvalidOracleAccount));
@@ -39,4 +95,4 @@ impl OracleManager {
         
         Ok(())
     }
-} 
\ No newline at end of file
+}
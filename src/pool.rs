@@ -1,66 +1,570 @@
 use anchor_lang::prelude::*;
-use crate::error::DexError;
+use crate::{
+    config::{validate_pause_flags, PAUSE_SWAP},
+    curve::CurveType,
+    error::DexError,
+    fixed_point::{FixedPrice, BPS_DENOMINATOR},
+    oracle::OraclePrice,
+};
+
+// Oracle move (in basis points) that triggers a pool rebalance
+pub const REBALANCE_THRESHOLD_BPS: u64 = 100;
+
+// Upper bound on the swap fee a pool admin can configure (10%)
+pub const MAX_FEE_BPS: u16 = 1_000;
+
+// Upper bound on the liquidity concentration around the curve price
+pub const MAX_CONCENTRATION_FACTOR: u8 = 100;
+
+// Upper bound on how far a rebalance may shift the curve price from the oracle (5%)
+pub const MAX_REBALANCE_CAP_BPS: u16 = 500;
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Debug)]
-pub enum TokenType {
-    SOL,
-    USDC,
+pub enum SwapDirection {
+    BaseToQuote,
+    QuoteToBase,
+}
+
+// Per-pool settings supplied at creation
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Debug)]
+pub struct InitializePoolParams {
+    pub concentration_factor: u8,
+    pub max_confidence_bps: u16,
+    pub confidence_multiplier_bps: u16,
+    pub max_oracle_age_slots: u64,
+    pub max_oracle_age_secs: u64,
+    pub fee_bps: Option<u16>,   // Falls back to config.default_fee_bps
+    pub target_base_bps: u16,
+    pub rebalance_cap_bps: u16,
+    pub curve_type: CurveType,
+    pub base_spread_bps: u16,
+    pub min_fee_bps: u16,       // Dynamic fee bounds, a max of 0 keeps fee_bps flat
+    pub max_fee_bps: u16,
+}
+
+// Swap request shared by the direct and delegated swap instructions
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Debug)]
+pub struct SwapParams {
+    pub direction: SwapDirection,
+    pub amount_in: u64,
+    pub min_amount_out: u64,
+    pub max_price_impact_bps: u16,  // Max shortfall of the execution price vs the oracle, 0 disables
+}
+
+// Exact-output swap request: `amount_out` is delivered for at most `max_amount_in`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Debug)]
+pub struct SwapExactOutParams {
+    pub direction: SwapDirection,
+    pub amount_out: u64,
+    pub max_amount_in: u64,
+    pub max_price_impact_bps: u16,
+}
+
+// Shortfall of `amount_out` against the output `amount_in` buys at `price`, in basis points.
+// Covers curve slippage, the confidence spread and the swap fee.
+pub fn price_impact_bps(
+    price: &FixedPrice,
+    direction: SwapDirection,
+    amount_in: u64,
+    amount_out: u64,
+) -> Result<u64> {
+    let fair_out = match direction {
+        SwapDirection::BaseToQuote => price.mul_u64(amount_in)?,
+        SwapDirection::QuoteToBase => price.div_u64(amount_in)?,
+    };
+    if fair_out == 0 || amount_out >= fair_out {
+        return Ok(0);
+    }
+    let shortfall = (fair_out - amount_out) as u128;
+    Ok((shortfall * BPS_DENOMINATOR as u128 / fair_out as u128) as u64)
 }
 
 #[account]
+#[derive(Default)]
 pub struct Pool {
-    pub sol_reserve: u64,
-    pub usdc_reserve: u64,
-    pub oracle_price: f64,
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub base_vault: Pubkey,
+    pub quote_vault: Pubkey,
+    pub base_decimals: u8,
+    pub quote_decimals: u8,
+    pub oracle: Pubkey,                 // Price feed quoting base in units of quote
+    pub base_reserve: u64,
+    pub quote_reserve: u64,
+    pub oracle_price: FixedPrice,       // Quote atoms per base atom
     pub concentration_factor: u8,
     pub bump: u8,
-    pub magic_block_delegate: Pubkey,
+    pub authority: Pubkey,
+    pub lp_mint: Pubkey,
+    pub delegated: bool,
+    pub max_confidence_bps: u16,        // Max oracle conf/price before swaps are rejected, 0 disables
+    pub confidence_multiplier_bps: u16, // k in price -/+ k*conf, 10_000 = 1.0
+    pub max_oracle_age_slots: u64,
+    pub max_oracle_age_secs: u64,
+    pub fee_bps: u16,                   // Swap fee charged on the input amount
+    pub fees_collected_base: u64,       // Cumulative fees charged on base input
+    pub fees_collected_quote: u64,      // Cumulative fees charged on quote input
+    pub protocol_fees_base: u64,        // Unclaimed protocol fees held in the base vault
+    pub protocol_fees_quote: u64,       // Unclaimed protocol fees held in the quote vault
+    pub paused: u8,                     // Operations paused for this pool, see PAUSE_*
+    pub breaker_threshold_bps: u16,     // Oracle move within the window that pauses swaps, 0 disables
+    pub breaker_window_slots: u64,
+    pub breaker_ref_price: FixedPrice,  // Oracle price at the start of the current window
+    pub breaker_ref_slot: u64,
+    pub target_base_bps: u16,           // Target share of pool value held in base, 5_000 = 50/50
+    pub rebalance_cap_bps: u16,         // Max shift of the curve price from the oracle towards the target
+    pub curve_base_reserve: u64,        // Virtual reserves the curve trades on, re-centered on rebalance
+    pub curve_quote_reserve: u64,
+    pub curve_type: CurveType,
+    pub base_spread_bps: u16,           // Oracle-anchored curves only: spread quoted around the mid
+    pub min_fee_bps: u16,               // Bounds of the dynamic fee, a max of 0 charges fee_bps flat
+    pub max_fee_bps: u16,
+    pub volatility_bps: u64,            // Moving average of annualized oracle volatility
+    pub volatility_ref_price: FixedPrice, // Oracle price at the last volatility sample
+    pub volatility_ref_slot: u64,
+    pub twap_gap_bps: u64,              // Distance of the oracle price from its TWAP
+    pub lazer_feed_id: u32,             // Pyth Lazer feed for `swap_lazer`, 0 when disabled
+    pub lazer_timestamp_us: u64,        // Publish time of the last Lazer price used, blocks replays
 }
 
 impl Pool {
-    pub const LEN: usize = 8 + 8 + 8 + 1 + 1 + 32; // size of each field
+    pub const LEN: usize = 32 + 32 + 32 + 32 + 1 + 1 + 32 + 8 + 8 + 16 + 1 + 1 + 32 + 32 + 1 + 2 + 2 + 8 + 8 + 2 + 8 + 8 + 8 + 8 + 1 + 2 + 8 + 16 + 8 + 2 + 2 + 8 + 8 + 1 + 2 + 2 + 2 + 8 + 16 + 8 + 8 + 4 + 8; // size of each field
     
-    pub fn rebalance(&mut self, new_price: f64) {
-        let target_usdc = (self.sol_reserve as f64 * new_price) as u64;
-        self.usdc_reserve = target_usdc;
+    // Seeds of the pool PDA, which owns the vaults and the LP mint
+    pub fn signer_seeds(&self) -> [&[u8]; 5] {
+        [
+            b"pool",
+            self.authority.as_ref(),
+            self.base_mint.as_ref(),
+            self.quote_mint.as_ref(),
+            std::slice::from_ref(&self.bump),
+        ]
+    }
+
+    // Re-center the curve on a new oracle price and return the curve price. Only the
+    // virtual curve moves: real reserves change through trades and liquidity alone.
+    pub fn rebalance(&mut self, new_price: FixedPrice) -> Result<FixedPrice> {
         self.oracle_price = new_price;
+        self.recenter()
     }
 
-    pub fn compute_swap_output(&self, input_token: TokenType, amount_in: u64) -> Result<(u64, u64)> {
-        let (reserve_in, reserve_out) = match input_token {
-            TokenType::SOL => (self.sol_reserve, self.usdc_reserve),
-            TokenType::USDC => (self.usdc_reserve, self.sol_reserve),
+    // Rebuild the virtual curve with the pool's current value split evenly at the curve
+    // price, so its marginal price starts at the curve price. Both sides are amplified by
+    // the concentration factor, giving c-fold depth around that price (k' = k * c^2).
+    pub fn recenter(&mut self) -> Result<FixedPrice> {
+        let curve_price = self.curve_price()?;
+        let half_value = self.value_in_quote(self.base_reserve, self.quote_reserve)? / 2;
+        let curve_quote = half_value
+            .checked_mul(self.concentration_factor as u128)
+            .ok_or(error!(DexError::ArithmeticOverflow))?;
+        self.curve_quote_reserve = u64::try_from(curve_quote)
+            .map_err(|_| error!(DexError::ArithmeticOverflow))?;
+        self.curve_base_reserve = if curve_price.is_zero() {
+            0
+        } else {
+            curve_price.div_u64(self.curve_quote_reserve)?
         };
+        Ok(curve_price)
+    }
 
-        // Apply concentration factor to virtual reserves
-        let k = (reserve_in as u128)
-            .checked_mul(reserve_out as u128)
-            .ok_or(error!(DexError::ArithmeticOverflow))?;
-        
-        let virtual_reserve_in = reserve_in as u128 * self.concentration_factor as u128;
-        let virtual_reserve_out = k.checked_div(virtual_reserve_in)
-            .ok_or(error!(DexError::ArithmeticOverflow))?;
+    // Oracle price shifted against the inventory skew by at most rebalance_cap_bps, so
+    // trades pull the pool back towards its target value split
+    pub fn curve_price(&self) -> Result<FixedPrice> {
+        let base_value_bps = self.base_value_bps()?;
+        let target_bps = self.target_base_bps as u64;
+        let cap_bps = self.rebalance_cap_bps as u64;
+        if base_value_bps > target_bps {
+            // Too much base: discount it so buyers take it off the pool
+            self.oracle_price.mul_bps(BPS_DENOMINATOR - (base_value_bps - target_bps).min(cap_bps))
+        } else {
+            self.oracle_price.mul_bps(BPS_DENOMINATOR + (target_bps - base_value_bps).min(cap_bps))
+        }
+    }
 
-        let amount_in_with_fee = amount_in as u128 * 997; // 0.3% fee
-        let numerator = amount_in_with_fee.checked_mul(virtual_reserve_out)
-            .ok_or(error!(DexError::ArithmeticOverflow))?;
-        let denominator = virtual_reserve_in.checked_mul(1000)
+    // Share of pool value held in base at the pool price, in basis points
+    pub fn base_value_bps(&self) -> Result<u64> {
+        let total_value = self.value_in_quote(self.base_reserve, self.quote_reserve)?;
+        if total_value == 0 {
+            return Ok(self.target_base_bps as u64);
+        }
+        let base_value = self.oracle_price.mul_u64(self.base_reserve)? as u128;
+        Ok((base_value * BPS_DENOMINATOR as u128 / total_value) as u64)
+    }
+
+    // Distance of the base value share from target in bps, and whether a swap in
+    // `direction` moves the pool further from it
+    pub fn inventory_skew_bps(&self, direction: SwapDirection) -> Result<(u64, bool)> {
+        let base_value_bps = self.base_value_bps()?;
+        let target_bps = self.target_base_bps as u64;
+        let worsens_skew = match direction {
+            SwapDirection::BaseToQuote => base_value_bps > target_bps,
+            SwapDirection::QuoteToBase => base_value_bps < target_bps,
+        };
+        Ok((base_value_bps.abs_diff(target_bps), worsens_skew))
+    }
+
+    pub fn set_concentration_factor(&mut self, concentration_factor: u8) -> Result<()> {
+        if concentration_factor == 0 || concentration_factor > MAX_CONCENTRATION_FACTOR {
+            msg!("Concentration factor {} must be between 1 and {}",
+                concentration_factor, MAX_CONCENTRATION_FACTOR);
+            return Err(error!(DexError::InvalidConcentrationFactor));
+        }
+        self.concentration_factor = concentration_factor;
+        Ok(())
+    }
+
+    pub fn set_rebalance_params(&mut self, target_base_bps: u16, rebalance_cap_bps: u16) -> Result<()> {
+        if target_base_bps == 0
+            || target_base_bps as u64 >= BPS_DENOMINATOR
+            || rebalance_cap_bps > MAX_REBALANCE_CAP_BPS
+        {
+            msg!("Invalid rebalance target {} bps or cap {} bps (max {} bps)",
+                target_base_bps, rebalance_cap_bps, MAX_REBALANCE_CAP_BPS);
+            return Err(error!(DexError::InvalidRebalanceParams));
+        }
+        self.target_base_bps = target_base_bps;
+        self.rebalance_cap_bps = rebalance_cap_bps;
+        Ok(())
+    }
+
+    // Whether the oracle has moved far enough from the pool price to rebalance
+    pub fn needs_rebalance(&self, new_price: &FixedPrice) -> Result<(bool, u64)> {
+        let change_bps = self.oracle_price.change_bps(new_price)?;
+        // Oracle-anchored curves quote from the pool price, so they follow every move
+        let threshold_bps = match self.curve_type {
+            CurveType::ConstantProduct => REBALANCE_THRESHOLD_BPS,
+            CurveType::OracleAnchored => 0,
+        };
+        Ok((change_bps > threshold_bps, change_bps))
+    }
+
+    // Reject swaps while the oracle's confidence interval is too wide relative to its price
+    pub fn check_confidence(&self, oracle_price: &OraclePrice) -> Result<()> {
+        if self.max_confidence_bps == 0 {
+            return Ok(());
+        }
+        let confidence_bps = oracle_price.confidence_bps()?;
+        if confidence_bps > self.max_confidence_bps as u64 {
+            msg!("Oracle confidence {} bps exceeds pool limit of {} bps",
+                confidence_bps, self.max_confidence_bps);
+            return Err(error!(DexError::OracleConfidenceTooWide));
+        }
+        Ok(())
+    }
+
+    // Quote against the edge of the confidence band: sellers of base receive
+    // price - k*conf, buyers of base pay price + k*conf
+    pub fn apply_confidence_spread(
+        &self,
+        direction: SwapDirection,
+        amount_out: u64,
+        oracle_price: &OraclePrice,
+    ) -> Result<u64> {
+        let (numerator, denominator) = self.confidence_spread_ratio(direction, oracle_price)?;
+        FixedPrice::mul_ratio_u64(amount_out, &numerator, &denominator)
+    }
+
+    // Inverse of apply_confidence_spread: the smallest curve output that still pays `amount_out`
+    pub fn remove_confidence_spread(
+        &self,
+        direction: SwapDirection,
+        amount_out: u64,
+        oracle_price: &OraclePrice,
+    ) -> Result<u64> {
+        let (numerator, denominator) = self.confidence_spread_ratio(direction, oracle_price)?;
+        FixedPrice::mul_ratio_u64_ceil(amount_out, &denominator, &numerator)
+    }
+
+    // Output is scaled by bid/price when selling base and price/ask when buying it
+    fn confidence_spread_ratio(
+        &self,
+        direction: SwapDirection,
+        oracle_price: &OraclePrice,
+    ) -> Result<(FixedPrice, FixedPrice)> {
+        let spread = oracle_price.confidence.mul_bps(self.confidence_multiplier_bps as u64)?;
+        let price = oracle_price.price;
+
+        match direction {
+            SwapDirection::BaseToQuote => {
+                let bid = price.checked_sub(spread)
+                    .map_err(|_| error!(DexError::OracleConfidenceTooWide))?;
+                Ok((bid, price))
+            }
+            SwapDirection::QuoteToBase => {
+                let ask = price.checked_add(spread)?;
+                Ok((price, ask))
+            }
+        }
+    }
+
+    // Fail if `operation` is paused either for this pool or globally
+    pub fn check_not_paused(&self, global_paused: u8, operation: u8) -> Result<()> {
+        if (self.paused | global_paused) & operation != 0 {
+            msg!("Operation {:#06b} is paused (pool {:#06b}, global {:#06b})",
+                operation, self.paused, global_paused);
+            return Err(error!(DexError::PoolPaused));
+        }
+        Ok(())
+    }
+
+    pub fn set_paused(&mut self, paused: u8) -> Result<()> {
+        validate_pause_flags(paused)?;
+        self.paused = paused;
+        if paused & PAUSE_SWAP == 0 {
+            // Resuming swaps starts a fresh breaker window
+            self.breaker_ref_price = FixedPrice::ZERO;
+        }
+        Ok(())
+    }
+
+    // A threshold of 0 disables the breaker; it does not lift a pause it already caused
+    pub fn set_circuit_breaker(&mut self, threshold_bps: u16, window_slots: u64) {
+        self.breaker_threshold_bps = threshold_bps;
+        self.breaker_window_slots = window_slots;
+        // Start a fresh window on the next swap
+        self.breaker_ref_price = FixedPrice::ZERO;
+    }
+
+    // Track oracle moves over a window of slots. Returns true, and pauses swaps,
+    // when the price moved more than the threshold since the window started.
+    pub fn check_circuit_breaker(&mut self, price: FixedPrice, slot: u64) -> Result<bool> {
+        if self.breaker_threshold_bps == 0 {
+            return Ok(false);
+        }
+
+        let window_expired = slot.saturating_sub(self.breaker_ref_slot) > self.breaker_window_slots;
+        if self.breaker_ref_price.is_zero() || window_expired {
+            self.breaker_ref_price = price;
+            self.breaker_ref_slot = slot;
+            return Ok(false);
+        }
+
+        let change_bps = self.breaker_ref_price.change_bps(&price)?;
+        if change_bps > self.breaker_threshold_bps as u64 {
+            msg!("Oracle moved {} bps within {} slots - pausing swaps",
+                change_bps, slot - self.breaker_ref_slot);
+            self.paused |= PAUSE_SWAP;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    pub fn set_fee_bps(&mut self, fee_bps: u16) -> Result<()> {
+        if fee_bps > MAX_FEE_BPS {
+            msg!("Fee of {} bps exceeds maximum of {} bps", fee_bps, MAX_FEE_BPS);
+            return Err(error!(DexError::InvalidFeeBps));
+        }
+        self.fee_bps = fee_bps;
+        Ok(())
+    }
+
+    // Fee charged on `amount_in` at the dynamic fee rate, rounded up so small swaps cannot avoid it
+    pub fn compute_fee(&self, direction: SwapDirection, amount_in: u64) -> Result<u64> {
+        let fee = (amount_in as u128)
+            .checked_mul(self.dynamic_fee_bps(direction)? as u128)
             .ok_or(error!(DexError::ArithmeticOverflow))?
-            .checked_add(amount_in_with_fee)
+            + (BPS_DENOMINATOR as u128 - 1);
+        Ok((fee / BPS_DENOMINATOR as u128) as u64)
+    }
+
+    // Protocol share of a swap fee at the config's current rate, rounded down in favour of LPs
+    pub fn compute_protocol_fee(fee: u64, protocol_fee_bps: u16) -> u64 {
+        ((fee as u128 * protocol_fee_bps as u128) / BPS_DENOMINATOR as u128) as u64
+    }
+
+    // Real reserve a swap in `direction` pays out of
+    fn reserve_out(&self, direction: SwapDirection) -> u64 {
+        match direction {
+            SwapDirection::BaseToQuote => self.quote_reserve,
+            SwapDirection::QuoteToBase => self.base_reserve,
+        }
+    }
+
+    // Returns (amount_out, fee); only `amount_in - fee` is priced against the curve.
+    // The curve is amplified, so the output is capped by what the pool actually holds.
+    pub fn compute_swap_output(&self, direction: SwapDirection, amount_in: u64) -> Result<(u64, u64)> {
+        let fee = self.compute_fee(direction, amount_in)?;
+        let amount_in_after_fee = (amount_in - fee) as u128;
+        let amount_out = self.curve_output(direction, amount_in_after_fee)?;
+        let amount_out = amount_out.min(self.reserve_out(direction) as u128) as u64;
+
+        Ok((amount_out, fee))
+    }
+
+    // Inverse of compute_swap_output: returns (amount_in, fee) for the smallest input
+    // whose output is at least `amount_out`. Both steps round up, in the pool's favour.
+    pub fn compute_swap_input(&self, direction: SwapDirection, amount_out: u64) -> Result<(u64, u64)> {
+        let reserve_out = self.reserve_out(direction);
+        if amount_out > reserve_out {
+            msg!("Requested output {} exceeds reserve {}", amount_out, reserve_out);
+            return Err(error!(DexError::InsufficientLiquidity));
+        }
+        let amount_in_after_fee = self.curve_input(direction, amount_out as u128)?;
+
+        // Gross up for the fee; compute_fee rounds up, so `amount_in - fee` still
+        // covers `amount_in_after_fee`
+        let fee_denominator = (BPS_DENOMINATOR - self.dynamic_fee_bps(direction)?) as u128;
+        let amount_in = amount_in_after_fee
+            .checked_mul(BPS_DENOMINATOR as u128)
+            .and_then(|amount| amount.checked_add(fee_denominator - 1))
             .ok_or(error!(DexError::ArithmeticOverflow))?;
+        let amount_in = u64::try_from(amount_in / fee_denominator)
+            .map_err(|_| error!(DexError::ArithmeticOverflow))?;
+
+        let fee = self.compute_fee(direction, amount_in)?;
+        Ok((amount_in, fee))
+    }
 
-        let amount_out = numerator.checked_div(denominator)
-            .ok_or(error!(DexError::ArithmeticOverflow))? as u64;
+    // Value of a base/quote amount pair denominated in quote at the pool's oracle price
+    pub fn value_in_quote(&self, base_amount: u64, quote_amount: u64) -> Result<u128> {
+        let base_value = self.oracle_price.mul_u64(base_amount)? as u128;
+        base_value.checked_add(quote_amount as u128)
+            .ok_or(error!(DexError::ArithmeticOverflow))
+    }
+
+    // LP shares to mint for a deposit, proportional to the value it adds to the pool. Whatever
+    // is deposited beyond the pool's reserve ratio is a swap in disguise, so it pays the swap
+    // fee; the fee stays in the pool for existing LPs.
+    pub fn compute_lp_shares(&self, base_amount: u64, quote_amount: u64, lp_supply: u64) -> Result<u64> {
+        let deposit_value = self.value_in_quote(base_amount, quote_amount)?;
+
+        // First deposit sets the share price at 1 share per quote atom of value
+        let shares = if lp_supply == 0 {
+            deposit_value
+        } else {
+            let pool_value = self.value_in_quote(self.base_reserve, self.quote_reserve)?;
+            if pool_value == 0 {
+                return Err(error!(DexError::InsufficientLiquidity));
+            }
+            let fee_value = self.imbalance_fee_value(base_amount, quote_amount)?;
+            deposit_value.saturating_sub(fee_value)
+                .checked_mul(lp_supply as u128)
+                .ok_or(error!(DexError::ArithmeticOverflow))?
+                .checked_div(pool_value)
+                .ok_or(error!(DexError::ArithmeticOverflow))?
+        };
+
+        u64::try_from(shares).map_err(|_| error!(DexError::ArithmeticOverflow))
+    }
+
+    // Swap fee, in quote, on the part of a deposit that does not match the reserve ratio.
+    // The excess is charged as if it had been swapped in, and the fee is rounded up.
+    pub fn imbalance_fee_value(&self, base_amount: u64, quote_amount: u64) -> Result<u128> {
+        let base_side = base_amount as u128 * self.quote_reserve as u128;
+        let quote_side = quote_amount as u128 * self.base_reserve as u128;
+
+        if base_side > quote_side {
+            // quote_reserve > 0 here, and the balanced base is below base_amount
+            let balanced_base = quote_amount as u128 * self.base_reserve as u128 / self.quote_reserve as u128;
+            let fee = self.compute_fee(SwapDirection::BaseToQuote, base_amount - balanced_base as u64)?;
+            Ok(self.oracle_price.mul_u64_ceil(fee)? as u128)
+        } else if quote_side > base_side {
+            let balanced_quote = base_amount as u128 * self.quote_reserve as u128 / self.base_reserve as u128;
+            Ok(self.compute_fee(SwapDirection::QuoteToBase, quote_amount - balanced_quote as u64)? as u128)
+        } else {
+            Ok(0)
+        }
+    }
+
+    // Pro-rata share of the reserves redeemed by burning `lp_amount` shares. Reserves exclude
+    // unclaimed protocol fees, and deposits are priced against them too, so both sides of an
+    // LP position share one source of truth. Callers sync the reserves to the vaults first,
+    // so this is a pro-rata share of the vault balances less the protocol fees.
+    pub fn compute_withdrawal(&self, lp_amount: u64, lp_supply: u64) -> Result<(u64, u64)> {
+        if lp_supply == 0 || lp_amount > lp_supply {
+            return Err(error!(DexError::InsufficientLiquidity));
+        }
+
+        let base_out = (self.base_reserve as u128)
+            .checked_mul(lp_amount as u128)
+            .ok_or(error!(DexError::ArithmeticOverflow))?
+            / lp_supply as u128;
+        let quote_out = (self.quote_reserve as u128)
+            .checked_mul(lp_amount as u128)
+            .ok_or(error!(DexError::ArithmeticOverflow))?
+            / lp_supply as u128;
+
+        Ok((base_out as u64, quote_out as u64))
+    }
+
+    // Reset the unclaimed protocol fees, returning (base, quote) for transfer to the treasury
+    pub fn take_protocol_fees(&mut self) -> (u64, u64) {
+        let fees = (self.protocol_fees_base, self.protocol_fees_quote);
+        self.protocol_fees_base = 0;
+        self.protocol_fees_quote = 0;
+        fees
+    }
+
+    // Fold vault balances beyond the reserves and unclaimed protocol fees, such as donations or
+    // rounding dust, into the reserves so LPs can redeem them
+    pub fn sync_reserves(&mut self, base_vault_amount: u64, quote_vault_amount: u64) -> Result<()> {
+        self.check_vault_invariant(base_vault_amount, quote_vault_amount)?;
+        self.base_reserve = base_vault_amount - self.protocol_fees_base;
+        self.quote_reserve = quote_vault_amount - self.protocol_fees_quote;
+        Ok(())
+    }
 
-        let fee = (amount_in as u128 * 3) / 1000; // 0.3% fee
-        Ok((amount_out, fee as u64))
+    // Vaults must always hold at least the reserves plus unclaimed protocol fees
+    pub fn check_vault_invariant(&self, base_vault_amount: u64, quote_vault_amount: u64) -> Result<()> {
+        let base_owed = self.base_reserve as u128 + self.protocol_fees_base as u128;
+        let quote_owed = self.quote_reserve as u128 + self.protocol_fees_quote as u128;
+        if (base_vault_amount as u128) < base_owed || (quote_vault_amount as u128) < quote_owed {
+            msg!("Vaults hold {} base / {} quote but {} base / {} quote are accounted",
+                base_vault_amount, quote_vault_amount, base_owed, quote_owed);
+            return Err(error!(DexError::VaultBelowReserves));
+        }
+        Ok(())
     }
 
-    pub fn update_reserves(&mut self, input_token: TokenType, amount_in: u64, fee: u64) {
-        let effective_input = amount_in.checked_sub(fee).unwrap();
-        match input_token {
-            TokenType::SOL => self.sol_reserve += effective_input,
-            TokenType::USDC => self.usdc_reserve += effective_input,
+    // Apply a settled swap. The input, fee included, stays in the reserves so fees accrue to LPs;
+    // only the protocol share of the fee, `protocol_fee_bps` from the config, is held outside
+    // them until claimed.
+    pub fn update_reserves(
+        &mut self,
+        direction: SwapDirection,
+        amount_in: u64,
+        amount_out: u64,
+        fee: u64,
+        protocol_fee_bps: u16,
+    ) -> Result<()> {
+        let protocol_fee = Pool::compute_protocol_fee(fee, protocol_fee_bps);
+        // Anchored pools price from the oracle and the real reserves, not the virtual curve
+        let tracks_curve = self.curve_type == CurveType::ConstantProduct;
+        let (reserve_in, reserve_out, curve_in, curve_out, fees_collected, protocol_fees) = match direction {
+            SwapDirection::BaseToQuote => (
+                &mut self.base_reserve,
+                &mut self.quote_reserve,
+                &mut self.curve_base_reserve,
+                &mut self.curve_quote_reserve,
+                &mut self.fees_collected_base,
+                &mut self.protocol_fees_base,
+            ),
+            SwapDirection::QuoteToBase => (
+                &mut self.quote_reserve,
+                &mut self.base_reserve,
+                &mut self.curve_quote_reserve,
+                &mut self.curve_base_reserve,
+                &mut self.fees_collected_quote,
+                &mut self.protocol_fees_quote,
+            ),
+        };
+
+        // The curve moves by what was priced against it, so impact persists until the next rebalance
+        if tracks_curve {
+            *curve_in = curve_in.checked_add(amount_in - fee)
+                .ok_or(error!(DexError::ArithmeticOverflow))?;
+            *curve_out = curve_out.checked_sub(amount_out)
+                .ok_or(error!(DexError::InsufficientLiquidity))?;
         }
+
+        *reserve_in = reserve_in.checked_add(amount_in - protocol_fee)
+            .ok_or(error!(DexError::ArithmeticOverflow))?;
+        *reserve_out = reserve_out.checked_sub(amount_out)
+            .ok_or(error!(DexError::InsufficientLiquidity))?;
+        *fees_collected = fees_collected.checked_add(fee)
+            .ok_or(error!(DexError::ArithmeticOverflow))?;
+        *protocol_fees = protocol_fees.checked_add(protocol_fee)
+            .ok_or(error!(DexError::ArithmeticOverflow))?;
+        Ok(())
     }
 } 
\ No newline at end of file
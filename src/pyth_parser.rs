@@ -1,18 +1,46 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{ed25519_program, sysvar::instructions::load_instruction_at_checked};
+use bytemuck::{Pod, Zeroable};
+use crate::{
+    error::DexError,
+    fixed_point::FixedPrice,
+};
 
 // SOL/USD price feed ID on Pyth Lazer
 pub const PYTH_LAZER_SOL_USD: &str = "7AxV2515SwLFVxWSpCngQ3TNqY17JERwcCfULc464u7D";
 
-#[derive(Copy, Clone)]
+// Pyth oracle program that owns legacy price accounts
+pub const PYTH_ORACLE_PROGRAM_ID: &str = "FsJ3A3u2vn5cTVofAjvy6y5kwABJAqYWpe4975bi2epH";
+
+// Legacy price account header values
+pub const PYTH_MAGIC: u32 = 0xa1b2_c3d4;
+pub const PYTH_VERSION_2: u32 = 2;
+pub const PYTH_ACCOUNT_TYPE_PRICE: u32 = 3;
+pub const PYTH_PRICE_TYPE_PRICE: u32 = 1;
+pub const PYTH_STATUS_TRADING: u32 = 1;
+
+// Exponentially-weighted moving average kept by the oracle program
+#[derive(Copy, Clone, Pod, Zeroable)]
+#[repr(C)]
+pub struct Ema {
+    pub val: i64,             // Current average, in the price exponent
+    pub numer: i64,           // Numerator state for the next update
+    pub denom: i64,           // Denominator state for the next update
+}
+
+#[derive(Copy, Clone, Pod, Zeroable)]
 #[repr(C)]
-pub struct Price {
-    pub price: i64,
-    pub conf: u64,
-    pub expo: i32,
-    pub publish_time: i64,
+pub struct PriceInfo {
+    pub price: i64,           // Price, in the account's exponent
+    pub conf: u64,            // Confidence interval, in the account's exponent
+    pub status: u32,          // Trading status, see PYTH_STATUS_*
+    pub corp_act: u32,        // Corporate action status, unused
+    pub pub_slot: u64,        // Slot this price was published in
 }
 
-#[derive(Copy, Clone)]
+// Header of a v2 price account, byte for byte: `agg` sits at offset 208. The per-publisher
+// components that follow it are not read.
+#[derive(Copy, Clone, Pod, Zeroable)]
 #[repr(C)]
 pub struct PriceMsg {
     pub magic: u32,           // Magic number to identify this as a valid Pyth price account
@@ -22,60 +50,398 @@ pub struct PriceMsg {
     pub price_type: u32,      // Type of price
     pub expo: i32,            // Price exponent
     pub num: u32,             // Number of component prices
-    pub unused: u32,          // Padding
-    pub curr_slot: u64,       // Currently accumulating price slot
+    pub num_qt: u32,          // Number of quoters that make up the aggregate
+    pub last_slot: u64,       // Slot of the last aggregate price update
     pub valid_slot: u64,      // Valid slot-time of agg. price
-    pub twap: i64,            // Time-weighted average price
-    pub avol: u64,            // Annualized price volatility
-    pub drv0: i64,            // Space for future derived values
-    pub drv1: i64,            // Space for future derived values
-    pub drv2: i64,            // Space for future derived values
-    pub drv3: i64,            // Space for future derived values
-    pub drv4: i64,            // Space for future derived values
-    pub drv5: i64,            // Space for future derived values
+    pub ema_price: Ema,       // Time-weighted average price
+    pub ema_conf: Ema,        // Time-weighted average confidence
+    pub timestamp: i64,       // Unix time of the aggregate price
+    pub min_pub: u8,          // Minimum publishers for a valid price
+    pub drv2: u8,             // Space for future derived values
+    pub drv3: u16,            // Space for future derived values
+    pub drv4: u32,            // Space for future derived values
     pub prod: [u8; 32],       // Product account key
     pub next: [u8; 32],       // Next price account in list
-    pub agg: Price,           // Aggregate price
-    pub pub_slot: u64,        // Last publication slot
+    pub prev_slot: u64,       // Valid slot of the previous aggregate
+    pub prev_price: i64,      // Previous aggregate price
+    pub prev_conf: u64,       // Previous aggregate confidence
+    pub prev_timestamp: i64,  // Unix time of the previous aggregate
+    pub agg: PriceInfo,       // Aggregate price
 }
 
 impl PriceMsg {
-    pub fn get_price(&self) -> f64 {
-        self.agg.price as f64 * 10f64.powi(self.expo)
+    // Reject accounts that are not a live, trading v2 price account
+    pub fn validate(&self) -> Result<()> {
+        if self.magic != PYTH_MAGIC {
+            msg!("Invalid Pyth magic: {:#x}", self.magic);
+            return Err(error!(DexError::InvalidOracleMagic));
+        }
+        if self.ver != PYTH_VERSION_2 {
+            msg!("Unsupported Pyth version: {}", self.ver);
+            return Err(error!(DexError::InvalidOracleVersion));
+        }
+        if self.atype != PYTH_ACCOUNT_TYPE_PRICE {
+            msg!("Pyth account is not a price account: {}", self.atype);
+            return Err(error!(DexError::InvalidOracleAccountType));
+        }
+        if self.price_type != PYTH_PRICE_TYPE_PRICE {
+            msg!("Unsupported Pyth price type: {}", self.price_type);
+            return Err(error!(DexError::InvalidOraclePriceType));
+        }
+        if self.agg.status != PYTH_STATUS_TRADING || self.num == 0 {
+            msg!("Pyth price is not trading: status {}, {} components", self.agg.status, self.num);
+            return Err(error!(DexError::OracleNotTrading));
+        }
+        if self.agg.price <= 0 {
+            msg!("Pyth aggregate price is not positive: {}", self.agg.price);
+            return Err(error!(DexError::InvalidOraclePrice));
+        }
+        Ok(())
     }
-    
-    pub fn get_confidence(&self) -> f64 {
-        self.agg.conf as f64 * 10f64.powi(self.expo)
+
+    pub fn get_price(&self) -> Result<FixedPrice> {
+        // Negative prices cannot be represented by an unsigned fixed-point price
+        if self.agg.price <= 0 {
+            return Err(error!(DexError::InvalidOraclePrice));
+        }
+        FixedPrice::from_decimal(self.agg.price as u64, self.expo)
     }
     
-    pub fn is_valid(&self, max_age_in_slots: u64) -> bool {
-        let age = self.curr_slot.saturating_sub(self.pub_slot);
-        age <= max_age_in_slots
+    pub fn get_confidence(&self) -> Result<FixedPrice> {
+        FixedPrice::from_decimal(self.agg.conf, self.expo)
+    }
+
+    // Time-weighted average price, None when the feed does not publish one
+    pub fn get_twap(&self) -> Result<Option<FixedPrice>> {
+        if self.ema_price.val <= 0 {
+            return Ok(None);
+        }
+        Ok(Some(FixedPrice::from_decimal(self.ema_price.val as u64, self.expo)?))
+    }
+
+    // Freshness is measured against the cluster clock, not the account's own slot counters,
+    // so a feed that stopped updating is detected
+    pub fn is_fresh(&self, clock: &Clock, max_age_slots: u64, max_age_secs: u64) -> bool {
+        let slot_age = clock.slot.saturating_sub(self.agg.pub_slot);
+        let time_age = clock.unix_timestamp.saturating_sub(self.timestamp);
+        slot_age <= max_age_slots && time_age <= max_age_secs as i64
     }
 }
 
 pub fn parse_pyth_price(account_data: &[u8]) -> Result<PriceMsg> {
-    if account_data.len() < std::mem::size_of::<PriceMsg>() {
+    let size = std::mem::size_of::<PriceMsg>();
+    if account_data.len() < size {
         return Err(error!(ErrorCode::AccountDidNotDeserialize));
     }
-    
-    // First part is the price data structure
-    let price_data: &PriceMsg = unsafe { 
-        &*(account_data.as_ptr() as *const PriceMsg) 
-    };
-    
-    Ok(*price_data)
+
+    // Account data carries no alignment guarantee, so copy the header out
+    Ok(bytemuck::pod_read_unaligned(&account_data[..size]))
 }
 
-pub fn get_pyth_price(price_account: &AccountInfo, max_age_in_slots: u64) -> Result<f64> {
+// Returns the price account once it is validated and fresh
+pub fn get_pyth_price(
+    price_account: &AccountInfo,
+    oracle_programs: &[Pubkey],
+    max_age_slots: u64,
+    max_age_secs: u64,
+) -> Result<PriceMsg> {
+    if !oracle_programs.contains(price_account.owner) {
+        msg!("Oracle account owned by {}, expected one of {:?}", price_account.owner, oracle_programs);
+        return Err(error!(DexError::InvalidOracleOwner));
+    }
+
     let data = price_account.try_borrow_data()?;
     let price_data = parse_pyth_price(&data)?;
+    price_data.validate()?;
     
-    if !price_data.is_valid(max_age_in_slots) {
-        msg!("Price data is not valid (too old)");
-        return Err(error!(crate::error::DexError::InvalidOracleData));
+    let clock = Clock::get()?;
+    if !price_data.is_fresh(&clock, max_age_slots, max_age_secs) {
+        msg!("Price data is not valid (too old): published at slot {} / {}, now slot {} / {}",
+            price_data.agg.pub_slot, price_data.timestamp, clock.slot, clock.unix_timestamp);
+        return Err(error!(DexError::InvalidOracleData));
     }
-    
-    let price = price_data.get_price();
-    Ok(price)
-} 
\ No newline at end of file
+
+    Ok(price_data)
+} 
+
+// Pyth Lazer Solana envelope: magic, Ed25519 signature, signer pubkey, payload
+pub const LAZER_SOLANA_FORMAT_MAGIC: u32 = 0x821a_01b9;
+pub const LAZER_PAYLOAD_FORMAT_MAGIC: u32 = 0x93c7_d375;
+
+// Lazer feed property identifiers
+pub const LAZER_PROPERTY_PRICE: u8 = 0;
+pub const LAZER_PROPERTY_BEST_BID_PRICE: u8 = 1;
+pub const LAZER_PROPERTY_BEST_ASK_PRICE: u8 = 2;
+pub const LAZER_PROPERTY_PUBLISHER_COUNT: u8 = 3;
+pub const LAZER_PROPERTY_EXPONENT: u8 = 4;
+pub const LAZER_PROPERTY_CONFIDENCE: u8 = 5;
+
+// Ed25519 program instruction layout
+const ED25519_OFFSETS_START: usize = 2;
+const ED25519_OFFSETS_LEN: usize = 14;
+// Instruction index meaning "data lives in the Ed25519 instruction itself"
+const ED25519_CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+// Signed Lazer message split into its parts
+pub struct LazerEnvelope<'a> {
+    pub signature: &'a [u8],
+    pub signer: Pubkey,
+    pub payload: &'a [u8],
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LazerFeed {
+    pub feed_id: u32,
+    pub price: Option<i64>,
+    pub best_bid_price: Option<i64>,
+    pub best_ask_price: Option<i64>,
+    pub publisher_count: Option<u16>,
+    pub exponent: Option<i16>,
+    pub confidence: Option<i64>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LazerPayload {
+    pub timestamp_us: u64,
+    pub channel_id: u8,
+    pub feeds: Vec<LazerFeed>,
+}
+
+// Verified price for a single feed
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LazerPrice {
+    pub feed_id: u32,
+    pub price: FixedPrice,
+    pub confidence: FixedPrice,
+    pub timestamp_us: u64,
+}
+
+impl LazerPrice {
+    pub fn is_fresh(&self, clock: &Clock, max_age_secs: u64) -> bool {
+        let publish_time = (self.timestamp_us / 1_000_000) as i64;
+        clock.unix_timestamp.saturating_sub(publish_time) <= max_age_secs as i64
+    }
+}
+
+impl LazerPayload {
+    pub fn get_price(&self, feed_id: u32) -> Result<LazerPrice> {
+        let feed = self.feeds.iter()
+            .find(|feed| feed.feed_id == feed_id)
+            .ok_or(error!(DexError::OracleFeedNotFound))?;
+
+        let exponent = feed.exponent.ok_or(error!(DexError::InvalidLazerMessage))? as i32;
+        let price = match feed.price {
+            Some(price) if price > 0 => FixedPrice::from_decimal(price as u64, exponent)?,
+            _ => return Err(error!(DexError::InvalidOracleData)),
+        };
+        let confidence = match feed.confidence {
+            Some(conf) if conf > 0 => FixedPrice::from_decimal(conf as u64, exponent)?,
+            _ => FixedPrice::ZERO,
+        };
+
+        Ok(LazerPrice {
+            feed_id,
+            price,
+            confidence,
+            timestamp_us: self.timestamp_us,
+        })
+    }
+}
+
+// Little-endian cursor over untrusted message bytes
+struct ByteReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        ByteReader { data, pos: 0 }
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self.pos.checked_add(len)
+            .ok_or(error!(DexError::InvalidLazerMessage))?;
+        let bytes = self.data.get(self.pos..end)
+            .ok_or(error!(DexError::InvalidLazerMessage))?;
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn read_u8(&mut self) -> Result<u8> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_u16(&mut self) -> Result<u16> {
+        Ok(u16::from_le_bytes(self.read_bytes(2)?.try_into().unwrap()))
+    }
+
+    fn read_i16(&mut self) -> Result<i16> {
+        Ok(i16::from_le_bytes(self.read_bytes(2)?.try_into().unwrap()))
+    }
+
+    fn read_u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.read_bytes(4)?.try_into().unwrap()))
+    }
+
+    fn read_u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.read_bytes(8)?.try_into().unwrap()))
+    }
+
+    fn read_i64(&mut self) -> Result<i64> {
+        Ok(i64::from_le_bytes(self.read_bytes(8)?.try_into().unwrap()))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos == self.data.len()
+    }
+}
+
+// Lazer encodes absent prices as zero
+fn non_zero(value: i64) -> Option<i64> {
+    if value == 0 { None } else { Some(value) }
+}
+
+pub fn parse_lazer_envelope(message: &[u8]) -> Result<LazerEnvelope> {
+    let mut reader = ByteReader::new(message);
+    if reader.read_u32()? != LAZER_SOLANA_FORMAT_MAGIC {
+        return Err(error!(DexError::InvalidLazerMessage));
+    }
+    let signature = reader.read_bytes(64)?;
+    let signer = Pubkey::new_from_array(reader.read_bytes(32)?.try_into().unwrap());
+    let payload_len = reader.read_u16()? as usize;
+    let payload = reader.read_bytes(payload_len)?;
+    if !reader.is_empty() {
+        return Err(error!(DexError::InvalidLazerMessage));
+    }
+
+    Ok(LazerEnvelope { signature, signer, payload })
+}
+
+pub fn parse_lazer_payload(payload: &[u8]) -> Result<LazerPayload> {
+    let mut reader = ByteReader::new(payload);
+    if reader.read_u32()? != LAZER_PAYLOAD_FORMAT_MAGIC {
+        return Err(error!(DexError::InvalidLazerMessage));
+    }
+    let timestamp_us = reader.read_u64()?;
+    let channel_id = reader.read_u8()?;
+    let num_feeds = reader.read_u8()?;
+
+    let mut feeds = Vec::with_capacity(num_feeds as usize);
+    for _ in 0..num_feeds {
+        let mut feed = LazerFeed {
+            feed_id: reader.read_u32()?,
+            ..Default::default()
+        };
+        let num_properties = reader.read_u8()?;
+        for _ in 0..num_properties {
+            match reader.read_u8()? {
+                LAZER_PROPERTY_PRICE => feed.price = non_zero(reader.read_i64()?),
+                LAZER_PROPERTY_BEST_BID_PRICE => feed.best_bid_price = non_zero(reader.read_i64()?),
+                LAZER_PROPERTY_BEST_ASK_PRICE => feed.best_ask_price = non_zero(reader.read_i64()?),
+                LAZER_PROPERTY_PUBLISHER_COUNT => feed.publisher_count = Some(reader.read_u16()?),
+                LAZER_PROPERTY_EXPONENT => feed.exponent = Some(reader.read_i16()?),
+                LAZER_PROPERTY_CONFIDENCE => feed.confidence = non_zero(reader.read_i64()?),
+                property => {
+                    msg!("Unknown Lazer feed property: {}", property);
+                    return Err(error!(DexError::InvalidLazerMessage));
+                }
+            }
+        }
+        feeds.push(feed);
+    }
+    if !reader.is_empty() {
+        return Err(error!(DexError::InvalidLazerMessage));
+    }
+
+    Ok(LazerPayload { timestamp_us, channel_id, feeds })
+}
+
+// Check that an Ed25519 program instruction verified exactly this signer, signature and payload.
+// The precompile has already rejected the transaction if the signature itself was invalid.
+pub fn verify_ed25519_instruction_data(
+    ix_data: &[u8],
+    signature: &[u8],
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    let mut reader = ByteReader::new(ix_data);
+    let num_signatures = reader.read_u8()
+        .map_err(|_| error!(DexError::InvalidSignatureInstruction))?;
+    if num_signatures != 1 {
+        return Err(error!(DexError::InvalidSignatureInstruction));
+    }
+
+    let offsets = ix_data
+        .get(ED25519_OFFSETS_START..ED25519_OFFSETS_START + ED25519_OFFSETS_LEN)
+        .ok_or(error!(DexError::InvalidSignatureInstruction))?;
+    let mut reader = ByteReader::new(offsets);
+    let mut read = || reader.read_u16().map_err(|_| error!(DexError::InvalidSignatureInstruction));
+    let signature_offset = read()? as usize;
+    let signature_ix_index = read()?;
+    let public_key_offset = read()? as usize;
+    let public_key_ix_index = read()?;
+    let message_offset = read()? as usize;
+    let message_size = read()? as usize;
+    let message_ix_index = read()?;
+
+    // Referencing other instructions would let the signed bytes differ from what we parse
+    if signature_ix_index != ED25519_CURRENT_INSTRUCTION
+        || public_key_ix_index != ED25519_CURRENT_INSTRUCTION
+        || message_ix_index != ED25519_CURRENT_INSTRUCTION
+    {
+        return Err(error!(DexError::InvalidSignatureInstruction));
+    }
+
+    let slice = |offset: usize, len: usize| {
+        ix_data.get(offset..offset.saturating_add(len))
+            .ok_or(error!(DexError::InvalidSignatureInstruction))
+    };
+    if slice(signature_offset, 64)? != signature
+        || slice(public_key_offset, 32)? != signer.as_ref()
+        || slice(message_offset, message_size)? != message
+    {
+        return Err(error!(DexError::InvalidSignatureInstruction));
+    }
+
+    Ok(())
+}
+
+// Decode a signed Lazer message and verify it against the Ed25519 instruction data
+pub fn verify_lazer_message(
+    ed25519_ix_data: &[u8],
+    message: &[u8],
+    trusted_signers: &[Pubkey],
+) -> Result<LazerPayload> {
+    let envelope = parse_lazer_envelope(message)?;
+
+    if !trusted_signers.contains(&envelope.signer) {
+        msg!("Untrusted Lazer signer: {}", envelope.signer);
+        return Err(error!(DexError::UntrustedOracleSigner));
+    }
+
+    verify_ed25519_instruction_data(
+        ed25519_ix_data,
+        envelope.signature,
+        &envelope.signer,
+        envelope.payload,
+    )?;
+
+    parse_lazer_payload(envelope.payload)
+}
+
+// Load the Ed25519 instruction at `ed25519_ix_index` from the instructions sysvar and verify
+pub fn get_lazer_payload(
+    instructions_sysvar: &AccountInfo,
+    ed25519_ix_index: u16,
+    message: &[u8],
+    trusted_signers: &[Pubkey],
+) -> Result<LazerPayload> {
+    let ix = load_instruction_at_checked(ed25519_ix_index as usize, instructions_sysvar)
+        .map_err(|_| error!(DexError::InvalidSignatureInstruction))?;
+    if ix.program_id != ed25519_program::ID {
+        return Err(error!(DexError::InvalidSignatureInstruction));
+    }
+
+    verify_lazer_message(&ix.data, message, trusted_signers)
+}
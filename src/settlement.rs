@@ -0,0 +1,313 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use crate::{
+    config::{Config, PAUSE_SWAP},
+    error::DexError,
+    events::{CircuitBreakerTripped, PoolRebalanced},
+    fixed_point::FixedPrice,
+    oracle::{OracleManager, OraclePrice},
+    pool::{price_impact_bps, Pool, SwapDirection, SwapExactOutParams, SwapParams},
+};
+
+// Priced swap, ready to be settled
+pub struct SwapQuote {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee: u64,
+    pub price: FixedPrice,      // Oracle price the swap was priced at
+}
+
+// Signed Pyth Lazer message, verified by the Ed25519 instruction at `ed25519_ix_index`
+pub struct LazerUpdate<'a, 'info> {
+    pub instructions_sysvar: &'a AccountInfo<'info>,
+    pub ed25519_ix_index: u16,
+    pub message: &'a [u8],
+}
+
+// Price an exact-input swap against the pool's oracle, or against `lazer` when given,
+// rebalancing first if the price has moved
+pub fn quote_swap(
+    pool: &mut Account<Pool>,
+    oracle: &UncheckedAccount,
+    lazer: Option<&LazerUpdate>,
+    config: &Config,
+    params: &SwapParams,
+    slot: u64,
+) -> Result<SwapQuote> {
+    let SwapParams { direction, amount_in, min_amount_out, max_price_impact_bps } = *params;
+    let oracle_price = refresh_oracle_price(pool, oracle, lazer, config, PAUSE_SWAP, slot)?;
+
+    // Calculate swap output with concentrated liquidity
+    let (amount_out, fee) = pool.compute_swap_output(direction, amount_in)?;
+    let amount_out = pool.apply_confidence_spread(direction, amount_out, &oracle_price)?;
+    msg!("Computed output: {} with fee: {}", amount_out, fee);
+
+    // Check slippage
+    if amount_out < min_amount_out {
+        msg!("Slippage exceeded: got {} but minimum is {}",
+            amount_out, min_amount_out);
+        return Err(error!(DexError::SlippageExceeded));
+    }
+
+    check_price_impact(&oracle_price, direction, amount_in, amount_out, max_price_impact_bps)?;
+
+    Ok(SwapQuote { amount_in, amount_out, fee, price: oracle_price.price })
+}
+
+// Exact-output counterpart of quote_swap: prices the input needed to pay `amount_out`
+pub fn quote_swap_exact_out(
+    pool: &mut Account<Pool>,
+    oracle: &UncheckedAccount,
+    config: &Config,
+    params: &SwapExactOutParams,
+    slot: u64,
+) -> Result<SwapQuote> {
+    let SwapExactOutParams { direction, amount_out, max_amount_in, max_price_impact_bps } = *params;
+    let oracle_price = refresh_oracle_price(pool, oracle, None, config, PAUSE_SWAP, slot)?;
+
+    // Invert the confidence spread, then the curve
+    let curve_out = pool.remove_confidence_spread(direction, amount_out, &oracle_price)?;
+    let (amount_in, fee) = pool.compute_swap_input(direction, curve_out)?;
+    msg!("Computed input: {} with fee: {}", amount_in, fee);
+
+    // Check slippage
+    if amount_in > max_amount_in {
+        msg!("Slippage exceeded: need {} but maximum is {}",
+            amount_in, max_amount_in);
+        return Err(error!(DexError::SlippageExceeded));
+    }
+
+    check_price_impact(&oracle_price, direction, amount_in, amount_out, max_price_impact_bps)?;
+
+    Ok(SwapQuote { amount_in, amount_out, fee, price: oracle_price.price })
+}
+
+// Checks shared by swaps and deposits: `operation`'s pause flag, oracle freshness and
+// confidence, the circuit breaker, then a rebalance if the oracle has moved. A trip fails
+// the operation, which also rolls back the pause; trip_circuit_breaker persists it.
+pub fn refresh_oracle_price(
+    pool: &mut Account<Pool>,
+    oracle: &UncheckedAccount,
+    lazer: Option<&LazerUpdate>,
+    config: &Config,
+    operation: u8,
+    slot: u64,
+) -> Result<OraclePrice> {
+    pool.check_not_paused(config.paused, operation)?;
+    let oracle_price = read_oracle_price(pool, oracle, lazer, config)?;
+    let new_price = oracle_price.price;
+    msg!("Current oracle price: {}, Pool price: {}", new_price, pool.oracle_price);
+
+    // Refuse to trade on an uncertain price
+    pool.check_confidence(&oracle_price)?;
+
+    if check_circuit_breaker(pool, new_price, slot)? {
+        return Err(error!(DexError::CircuitBreakerTripped));
+    }
+
+    // Feeds the volatility term of the dynamic fee
+    pool.record_volatility(&oracle_price, slot)?;
+
+    // Rebalance if price changes > 1%
+    let (should_rebalance, change_bps) = pool.needs_rebalance(&new_price)?;
+
+    if should_rebalance {
+        msg!("Price changed by {} bps - rebalancing pool", change_bps);
+        let old_price = pool.oracle_price;
+        let curve_price = pool.rebalance(new_price)?;
+        emit!(PoolRebalanced {
+            pool: pool.key(),
+            old_price,
+            new_price,
+            curve_price,
+            change_bps,
+            base_reserve: pool.base_reserve,
+            quote_reserve: pool.quote_reserve,
+            slot,
+        });
+    } else {
+        msg!("Price change ({} bps) below threshold - no rebalance needed",
+            change_bps);
+    }
+
+    Ok(oracle_price)
+}
+
+// Validated price from the pool's feed, or from `lazer` when given, in quote atoms per base atom
+pub fn read_oracle_price(
+    pool: &mut Account<Pool>,
+    oracle: &UncheckedAccount,
+    lazer: Option<&LazerUpdate>,
+    config: &Config,
+) -> Result<OraclePrice> {
+    OracleManager::validate_oracle_account(oracle, &pool.oracle)?;
+    match lazer {
+        Some(lazer) => fetch_lazer_price(pool, lazer, config)?,
+        None => OracleManager::fetch_price(
+            oracle,
+            &config.oracle_programs,
+            pool.max_oracle_age_slots,
+            pool.max_oracle_age_secs,
+        )?,
+    }.scale_decimals(pool.base_decimals, pool.quote_decimals)
+}
+
+// Run the pool's circuit breaker on `price`, announcing a trip
+pub fn check_circuit_breaker(pool: &mut Account<Pool>, price: FixedPrice, slot: u64) -> Result<bool> {
+    let reference = pool.breaker_ref_price;
+    let tripped = pool.check_circuit_breaker(price, slot)?;
+    if tripped {
+        emit!(CircuitBreakerTripped {
+            pool: pool.key(),
+            price,
+            reference,
+            slot,
+        });
+    }
+    Ok(tripped)
+}
+
+// Verify a Lazer price for the pool's feed, signed by one of the config's trusted signers.
+// Each message can move the pool forward in time only, so an older one cannot be replayed.
+fn fetch_lazer_price(pool: &mut Pool, lazer: &LazerUpdate, config: &Config) -> Result<OraclePrice> {
+    if pool.lazer_feed_id == 0 {
+        msg!("Pool has no Lazer feed");
+        return Err(error!(DexError::OracleFeedNotFound));
+    }
+
+    let lazer_price = OracleManager::fetch_lazer_price(
+        lazer.instructions_sysvar,
+        lazer.ed25519_ix_index,
+        lazer.message,
+        pool.lazer_feed_id,
+        &config.trusted_signers,
+        pool.max_oracle_age_secs,
+    )?;
+    if lazer_price.timestamp_us <= pool.lazer_timestamp_us {
+        msg!("Lazer price published at {} us, last used {} us",
+            lazer_price.timestamp_us, pool.lazer_timestamp_us);
+        return Err(error!(DexError::StaleLazerPrice));
+    }
+    pool.lazer_timestamp_us = lazer_price.timestamp_us;
+
+    Ok(OraclePrice {
+        price: lazer_price.price,
+        confidence: lazer_price.confidence,
+        twap: None,
+    })
+}
+
+// Fail if the execution price falls short of the oracle by more than `max_price_impact_bps`
+fn check_price_impact(
+    oracle_price: &OraclePrice,
+    direction: SwapDirection,
+    amount_in: u64,
+    amount_out: u64,
+    max_price_impact_bps: u16,
+) -> Result<()> {
+    if max_price_impact_bps == 0 {
+        return Ok(());
+    }
+    let impact_bps = price_impact_bps(&oracle_price.price, direction, amount_in, amount_out)?;
+    if impact_bps > max_price_impact_bps as u64 {
+        msg!("Price impact exceeded: {} bps but limit is {} bps",
+            impact_bps, max_price_impact_bps);
+        return Err(error!(DexError::PriceImpactExceeded));
+    }
+    Ok(())
+}
+
+// Token transfer CPI, signed by the pool PDA when `signer_seeds` is not empty
+fn transfer<'info>(
+    token_program: &Program<'info, Token>,
+    from: &Account<'info, TokenAccount>,
+    to: &Account<'info, TokenAccount>,
+    authority: AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+    amount: u64,
+) -> Result<()> {
+    let cpi_accounts = Transfer {
+        from: from.to_account_info(),
+        to: to.to_account_info(),
+        authority,
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        cpi_accounts,
+        signer_seeds,
+    );
+    if let Err(err) = token::transfer(cpi_ctx, amount) {
+        msg!("Error transferring {} from {} to {}: {:?}", amount, from.key(), to.key(), err);
+        return Err(error!(DexError::TokenTransferFailed));
+    }
+    Ok(())
+}
+
+// Move tokens from a user account into a pool vault, signed by the user
+pub fn transfer_to_vault<'info>(
+    token_program: &Program<'info, Token>,
+    from: &Account<'info, TokenAccount>,
+    vault: &Account<'info, TokenAccount>,
+    user: &Signer<'info>,
+    amount: u64,
+) -> Result<()> {
+    transfer(token_program, from, vault, user.to_account_info(), &[], amount)
+}
+
+// Pull swap input into a vault either with the owner's signature or, when the owner
+// is not a signer, through an SPL approval of the pool PDA as delegate for `amount`
+pub fn collect_input<'info>(
+    token_program: &Program<'info, Token>,
+    from: &Account<'info, TokenAccount>,
+    vault: &Account<'info, TokenAccount>,
+    user: &AccountInfo<'info>,
+    pool: &Account<'info, Pool>,
+    amount: u64,
+) -> Result<()> {
+    check_input_authority(from, &user.key(), user.is_signer, &pool.key(), amount)?;
+
+    if user.is_signer {
+        return transfer(token_program, from, vault, user.clone(), &[], amount);
+    }
+
+    let seeds = pool.signer_seeds();
+    transfer(token_program, from, vault, pool.to_account_info(), &[&seeds[..]], amount)
+}
+
+// The input account must belong to `user`, who either signs the transaction or has
+// approved the pool PDA as SPL delegate for at least `amount`
+pub fn check_input_authority(
+    from: &TokenAccount,
+    user: &Pubkey,
+    user_is_signer: bool,
+    pool: &Pubkey,
+    amount: u64,
+) -> Result<()> {
+    if from.owner != *user {
+        msg!("Input account owned by {} is not owned by {}", from.owner, user);
+        return Err(error!(DexError::InputNotAuthorized));
+    }
+    if user_is_signer {
+        return Ok(());
+    }
+
+    let approved = from.delegate == COption::Some(*pool) && from.delegated_amount >= amount;
+    if !approved {
+        msg!("Pool is not approved to move {} from {}'s account", amount, user);
+        return Err(error!(DexError::InputNotAuthorized));
+    }
+    Ok(())
+}
+
+// Move tokens out of a pool vault, signed by the pool PDA
+pub fn transfer_from_vault<'info>(
+    token_program: &Program<'info, Token>,
+    vault: &Account<'info, TokenAccount>,
+    to: &Account<'info, TokenAccount>,
+    pool: &Account<'info, Pool>,
+    amount: u64,
+) -> Result<()> {
+    let seeds = pool.signer_seeds();
+    transfer(token_program, vault, to, pool.to_account_info(), &[&seeds[..]], amount)
+}
@@ -0,0 +1,484 @@
+use std::collections::BTreeSet;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::token::spl_token;
+use bulker_dex::config::{Config, PAUSE_SWAP};
+use bulker_dex::error::DexError;
+use bulker_dex::fixed_point::FixedPrice;
+use bulker_dex::pool::Pool;
+use bulker_dex::settlement;
+use bulker_dex::{
+    AcceptAdmin, AcceptAdminBumps, DelegatedSwap, DelegatedSwapBumps, Swap, SwapBumps, TripCircuitBreaker,
+    TripCircuitBreakerBumps,
+};
+
+#[derive(Clone)]
+struct TestAccount {
+    key: Pubkey,
+    owner: Pubkey,
+    lamports: u64,
+    data: Vec<u8>,
+    is_signer: bool,
+    is_writable: bool,
+    executable: bool,
+}
+
+impl TestAccount {
+    fn new(key: Pubkey, owner: Pubkey, data: Vec<u8>) -> Self {
+        TestAccount {
+            key,
+            owner,
+            lamports: 1_000_000_000,
+            data,
+            is_signer: false,
+            is_writable: true,
+            executable: false,
+        }
+    }
+
+    fn signer(key: Pubkey) -> Self {
+        TestAccount {
+            is_signer: true,
+            ..TestAccount::new(key, system_program::ID, vec![])
+        }
+    }
+
+    fn program(key: Pubkey) -> Self {
+        TestAccount {
+            executable: true,
+            is_writable: false,
+            ..TestAccount::new(key, Pubkey::new_unique(), vec![])
+        }
+    }
+
+    fn info(&mut self) -> AccountInfo<'_> {
+        AccountInfo::new(
+            &self.key,
+            self.is_signer,
+            self.is_writable,
+            &mut self.lamports,
+            &mut self.data,
+            &self.owner,
+            self.executable,
+            0,
+        )
+    }
+}
+
+fn token_account(key: Pubkey, mint: Pubkey, owner: Pubkey) -> TestAccount {
+    let mut data = vec![0u8; spl_token::state::Account::LEN];
+    let account = spl_token::state::Account {
+        mint,
+        owner,
+        amount: 1_000_000_000,
+        state: spl_token::state::AccountState::Initialized,
+        ..Default::default()
+    };
+    spl_token::state::Account::pack(account, &mut data).unwrap();
+    TestAccount::new(key, spl_token::ID, data)
+}
+
+fn program_account<T: AccountSerialize>(key: Pubkey, account: &T) -> TestAccount {
+    let mut data = Vec::new();
+    account.try_serialize(&mut data).unwrap();
+    TestAccount::new(key, bulker_dex::ID, data)
+}
+
+// A correctly wired pool, its config and a trader
+struct Fixture {
+    authority: Pubkey,
+    base_mint: Pubkey,
+    quote_mint: Pubkey,
+    pool_key: Pubkey,
+    pool: Pool,
+    config_key: Pubkey,
+    config: Config,
+    user: Pubkey,
+    magic_block_authority: Pubkey,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        let authority = Pubkey::new_unique();
+        let base_mint = Pubkey::new_unique();
+        let quote_mint = Pubkey::new_unique();
+        let (pool_key, pool_bump) = Pubkey::find_program_address(
+            &[b"pool", authority.as_ref(), base_mint.as_ref(), quote_mint.as_ref()],
+            &bulker_dex::ID,
+        );
+        let (config_key, config_bump) = Pubkey::find_program_address(&[b"config"], &bulker_dex::ID);
+        let magic_block_authority = Pubkey::new_unique();
+
+        let pool = Pool {
+            base_mint,
+            quote_mint,
+            base_vault: Pubkey::new_unique(),
+            quote_vault: Pubkey::new_unique(),
+            oracle: Pubkey::new_unique(),
+            authority,
+            bump: pool_bump,
+            ..Default::default()
+        };
+        let config = Config {
+            magic_block_authority,
+            bump: config_bump,
+            ..Default::default()
+        };
+
+        Fixture {
+            authority,
+            base_mint,
+            quote_mint,
+            pool_key,
+            pool,
+            config_key,
+            config,
+            user: Pubkey::new_unique(),
+            magic_block_authority,
+        }
+    }
+
+    fn pool(&self) -> TestAccount {
+        program_account(self.pool_key, &self.pool)
+    }
+
+    fn config(&self) -> TestAccount {
+        program_account(self.config_key, &self.config)
+    }
+
+    fn oracle(&self) -> TestAccount {
+        TestAccount::new(self.pool.oracle, Pubkey::new_unique(), vec![0u8; 64])
+    }
+
+    fn user_base(&self) -> TestAccount {
+        token_account(Pubkey::new_unique(), self.base_mint, self.user)
+    }
+
+    fn user_quote(&self) -> TestAccount {
+        token_account(Pubkey::new_unique(), self.quote_mint, self.user)
+    }
+
+    fn base_vault(&self) -> TestAccount {
+        token_account(self.pool.base_vault, self.base_mint, self.pool_key)
+    }
+
+    fn quote_vault(&self) -> TestAccount {
+        token_account(self.pool.quote_vault, self.quote_mint, self.pool_key)
+    }
+
+    // In `Swap` field order
+    fn swap_accounts(&self) -> Vec<TestAccount> {
+        vec![
+            self.pool(),
+            self.config(),
+            self.oracle(),
+            TestAccount::signer(self.user),
+            self.user_base(),
+            self.user_quote(),
+            self.base_vault(),
+            self.quote_vault(),
+            TestAccount::program(spl_token::ID),
+        ]
+    }
+
+    // In `DelegatedSwap` field order
+    fn delegated_swap_accounts(&self) -> Vec<TestAccount> {
+        vec![
+            self.pool(),
+            self.oracle(),
+            self.config(),
+            TestAccount::signer(self.magic_block_authority),
+            TestAccount::new(self.user, system_program::ID, vec![]),
+            self.user_base(),
+            self.user_quote(),
+            self.base_vault(),
+            self.quote_vault(),
+            TestAccount::program(spl_token::ID),
+        ]
+    }
+}
+
+// Account indices in `Swap`
+const SWAP_POOL: usize = 0;
+const SWAP_CONFIG: usize = 1;
+const SWAP_ORACLE: usize = 2;
+const SWAP_USER_BASE: usize = 4;
+const SWAP_USER_QUOTE: usize = 5;
+const SWAP_BASE_VAULT: usize = 6;
+const SWAP_QUOTE_VAULT: usize = 7;
+
+// Account indices in `DelegatedSwap`
+const DELEGATED_POOL: usize = 0;
+const DELEGATED_ORACLE: usize = 1;
+const DELEGATED_MAGIC_BLOCK_AUTHORITY: usize = 3;
+const DELEGATED_USER_BASE: usize = 5;
+const DELEGATED_BASE_VAULT: usize = 7;
+
+fn try_swap_accounts(accounts: &mut [TestAccount]) -> Result<()> {
+    let infos: Vec<AccountInfo> = accounts.iter_mut().map(|a| a.info()).collect();
+    let mut remaining: &[AccountInfo] = &infos;
+    Swap::try_accounts(
+        &bulker_dex::ID,
+        &mut remaining,
+        &[],
+        &mut SwapBumps::default(),
+        &mut BTreeSet::new(),
+    )
+    .map(|_| ())
+}
+
+fn try_delegated_swap_accounts(accounts: &mut [TestAccount]) -> Result<()> {
+    let infos: Vec<AccountInfo> = accounts.iter_mut().map(|a| a.info()).collect();
+    let mut remaining: &[AccountInfo] = &infos;
+    DelegatedSwap::try_accounts(
+        &bulker_dex::ID,
+        &mut remaining,
+        &[],
+        &mut DelegatedSwapBumps::default(),
+        &mut BTreeSet::new(),
+    )
+    .map(|_| ())
+}
+
+fn try_trip_circuit_breaker_accounts(accounts: &mut [TestAccount]) -> Result<()> {
+    let infos: Vec<AccountInfo> = accounts.iter_mut().map(|a| a.info()).collect();
+    let mut remaining: &[AccountInfo] = &infos;
+    TripCircuitBreaker::try_accounts(
+        &bulker_dex::ID,
+        &mut remaining,
+        &[],
+        &mut TripCircuitBreakerBumps::default(),
+        &mut BTreeSet::new(),
+    )
+    .map(|_| ())
+}
+
+fn try_accept_admin_accounts(accounts: &mut [TestAccount]) -> Result<()> {
+    let infos: Vec<AccountInfo> = accounts.iter_mut().map(|a| a.info()).collect();
+    let mut remaining: &[AccountInfo] = &infos;
+    AcceptAdmin::try_accounts(
+        &bulker_dex::ID,
+        &mut remaining,
+        &[],
+        &mut AcceptAdminBumps::default(),
+        &mut BTreeSet::new(),
+    )
+    .map(|_| ())
+}
+
+#[test]
+fn test_swap_accepts_pool_accounts() {
+    let fixture = Fixture::new();
+    assert!(try_swap_accounts(&mut fixture.swap_accounts()).is_ok());
+}
+
+#[test]
+fn test_swap_rejects_pool_at_non_pda_address() {
+    let fixture = Fixture::new();
+    let mut accounts = fixture.swap_accounts();
+    accounts[SWAP_POOL].key = Pubkey::new_unique();
+    assert_eq!(
+        try_swap_accounts(&mut accounts).unwrap_err(),
+        Error::from(ErrorCode::ConstraintSeeds)
+    );
+}
+
+#[test]
+fn test_swap_rejects_pool_owned_by_other_program() {
+    let fixture = Fixture::new();
+    let mut accounts = fixture.swap_accounts();
+    accounts[SWAP_POOL].owner = Pubkey::new_unique();
+    assert_eq!(
+        try_swap_accounts(&mut accounts).unwrap_err(),
+        Error::from(ErrorCode::AccountOwnedByWrongProgram)
+    );
+}
+
+#[test]
+fn test_swap_rejects_substituted_config() {
+    let fixture = Fixture::new();
+    let mut accounts = fixture.swap_accounts();
+    accounts[SWAP_CONFIG].key = Pubkey::new_unique();
+    assert_eq!(
+        try_swap_accounts(&mut accounts).unwrap_err(),
+        Error::from(ErrorCode::ConstraintSeeds)
+    );
+}
+
+#[test]
+fn test_swap_rejects_substituted_oracle() {
+    let fixture = Fixture::new();
+    let mut accounts = fixture.swap_accounts();
+    accounts[SWAP_ORACLE].key = Pubkey::new_unique();
+    assert_eq!(
+        try_swap_accounts(&mut accounts).unwrap_err(),
+        Error::from(DexError::InvalidOracleAccount)
+    );
+}
+
+#[test]
+fn test_swap_rejects_user_account_with_wrong_mint() {
+    let fixture = Fixture::new();
+
+    let mut accounts = fixture.swap_accounts();
+    accounts[SWAP_USER_BASE] = token_account(Pubkey::new_unique(), fixture.quote_mint, fixture.user);
+    assert_eq!(
+        try_swap_accounts(&mut accounts).unwrap_err(),
+        Error::from(DexError::InvalidMint)
+    );
+
+    let mut accounts = fixture.swap_accounts();
+    accounts[SWAP_USER_QUOTE] = token_account(Pubkey::new_unique(), Pubkey::new_unique(), fixture.user);
+    assert_eq!(
+        try_swap_accounts(&mut accounts).unwrap_err(),
+        Error::from(DexError::InvalidMint)
+    );
+}
+
+#[test]
+fn test_swap_rejects_user_account_owned_by_someone_else() {
+    let fixture = Fixture::new();
+    let mut accounts = fixture.swap_accounts();
+    accounts[SWAP_USER_BASE] = token_account(Pubkey::new_unique(), fixture.base_mint, fixture.authority);
+    assert_eq!(
+        try_swap_accounts(&mut accounts).unwrap_err(),
+        Error::from(ErrorCode::ConstraintTokenOwner)
+    );
+}
+
+#[test]
+fn test_swap_rejects_substituted_vaults() {
+    let fixture = Fixture::new();
+
+    // A pool-owned account of the right mint that is not the stored vault
+    let mut accounts = fixture.swap_accounts();
+    accounts[SWAP_BASE_VAULT] = token_account(Pubkey::new_unique(), fixture.base_mint, fixture.pool_key);
+    assert_eq!(
+        try_swap_accounts(&mut accounts).unwrap_err(),
+        Error::from(ErrorCode::ConstraintHasOne)
+    );
+
+    // The stored vault must still be owned by the pool
+    let mut accounts = fixture.swap_accounts();
+    accounts[SWAP_QUOTE_VAULT] = token_account(fixture.pool.quote_vault, fixture.quote_mint, fixture.user);
+    assert_eq!(
+        try_swap_accounts(&mut accounts).unwrap_err(),
+        Error::from(ErrorCode::ConstraintTokenOwner)
+    );
+}
+
+#[test]
+fn test_delegated_swap_accepts_pool_accounts() {
+    let fixture = Fixture::new();
+    assert!(try_delegated_swap_accounts(&mut fixture.delegated_swap_accounts()).is_ok());
+}
+
+#[test]
+fn test_delegated_swap_rejects_pool_at_non_pda_address() {
+    let fixture = Fixture::new();
+    let mut accounts = fixture.delegated_swap_accounts();
+    accounts[DELEGATED_POOL].key = Pubkey::new_unique();
+    assert_eq!(
+        try_delegated_swap_accounts(&mut accounts).unwrap_err(),
+        Error::from(ErrorCode::ConstraintSeeds)
+    );
+}
+
+#[test]
+fn test_delegated_swap_rejects_substituted_oracle() {
+    let fixture = Fixture::new();
+    let mut accounts = fixture.delegated_swap_accounts();
+    accounts[DELEGATED_ORACLE].key = Pubkey::new_unique();
+    assert_eq!(
+        try_delegated_swap_accounts(&mut accounts).unwrap_err(),
+        Error::from(DexError::InvalidOracleAccount)
+    );
+}
+
+#[test]
+fn test_delegated_swap_rejects_untrusted_authority() {
+    let fixture = Fixture::new();
+    let mut accounts = fixture.delegated_swap_accounts();
+    accounts[DELEGATED_MAGIC_BLOCK_AUTHORITY] = TestAccount::signer(Pubkey::new_unique());
+    assert_eq!(
+        try_delegated_swap_accounts(&mut accounts).unwrap_err(),
+        Error::from(DexError::InvalidMagicBlockAuthority)
+    );
+}
+
+#[test]
+fn test_delegated_swap_rejects_user_account_with_wrong_mint() {
+    let fixture = Fixture::new();
+    let mut accounts = fixture.delegated_swap_accounts();
+    accounts[DELEGATED_USER_BASE] = token_account(Pubkey::new_unique(), fixture.quote_mint, fixture.user);
+    assert_eq!(
+        try_delegated_swap_accounts(&mut accounts).unwrap_err(),
+        Error::from(DexError::InvalidMint)
+    );
+}
+
+#[test]
+fn test_delegated_swap_rejects_substituted_vault() {
+    let fixture = Fixture::new();
+    let mut accounts = fixture.delegated_swap_accounts();
+    accounts[DELEGATED_BASE_VAULT] = token_account(Pubkey::new_unique(), fixture.base_mint, fixture.pool_key);
+    assert_eq!(
+        try_delegated_swap_accounts(&mut accounts).unwrap_err(),
+        Error::from(ErrorCode::ConstraintHasOne)
+    );
+}
+
+#[test]
+fn test_accept_admin_requires_pending_admin() {
+    let mut fixture = Fixture::new();
+    let pending_admin = Pubkey::new_unique();
+    fixture.config.propose_admin(pending_admin);
+
+    let mut accounts = vec![fixture.config(), TestAccount::signer(pending_admin)];
+    assert!(try_accept_admin_accounts(&mut accounts).is_ok());
+
+    let mut accounts = vec![fixture.config(), TestAccount::signer(Pubkey::new_unique())];
+    assert_eq!(
+        try_accept_admin_accounts(&mut accounts).unwrap_err(),
+        Error::from(DexError::Unauthorized)
+    );
+
+    // The pending admin must sign
+    let mut accounts = vec![fixture.config(), TestAccount::new(pending_admin, system_program::ID, vec![])];
+    assert_eq!(
+        try_accept_admin_accounts(&mut accounts).unwrap_err(),
+        Error::from(ErrorCode::AccountNotSigner)
+    );
+}
+
+#[test]
+fn test_trip_circuit_breaker_needs_pool_oracle() {
+    // Permissionless, but only against the pool's own feed
+    let fixture = Fixture::new();
+    let mut accounts = vec![fixture.pool(), fixture.config(), fixture.oracle()];
+    assert!(try_trip_circuit_breaker_accounts(&mut accounts).is_ok());
+
+    accounts[2].key = Pubkey::new_unique();
+    assert_eq!(
+        try_trip_circuit_breaker_accounts(&mut accounts).unwrap_err(),
+        Error::from(DexError::InvalidOracleAccount)
+    );
+}
+
+#[test]
+fn test_breaker_trip_pauses_swaps_and_keeps_reference() {
+    let mut fixture = Fixture::new();
+    fixture.pool.set_circuit_breaker(500, 100);
+    let mut pool_account = fixture.pool();
+    let info = pool_account.info();
+    let mut pool = Account::<Pool>::try_from(&info).unwrap();
+
+    assert!(!settlement::check_circuit_breaker(&mut pool, FixedPrice::from_int(100), 1).unwrap());
+    assert_eq!(pool.paused, 0);
+
+    // A 10% drop trips it; the event reports the window's reference price
+    assert!(settlement::check_circuit_breaker(&mut pool, FixedPrice::from_int(90), 2).unwrap());
+    assert_eq!(pool.paused, PAUSE_SWAP);
+    assert_eq!(pool.breaker_ref_price, FixedPrice::from_int(100));
+}
@@ -0,0 +1,108 @@
+// Helpers shared by the randomized and simulation tests. Each test crate
+// compiles its own copy, so helpers one file does not use are not dead code.
+#![allow(dead_code)]
+
+use bulker_dex::curve::CurveType;
+use bulker_dex::fixed_point::FixedPrice;
+use bulker_dex::pool::Pool;
+
+// Deterministic xorshift so failures, price paths and order flow are reproducible
+// without extra dependencies
+pub struct Rng(pub u64);
+
+impl Rng {
+    pub fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    pub fn range(&mut self, low: u64, high: u64) -> u64 {
+        low + self.next() % (high - low)
+    }
+}
+
+// Pool at 100 quote atoms per base atom with a 30 bps flat fee and no rebalance
+// target. Chain the overrides below to vary it, then `build`.
+pub fn pool(base_reserve: u64, quote_reserve: u64) -> PoolBuilder {
+    PoolBuilder(Pool {
+        oracle_price: FixedPrice::from_int(100),
+        base_reserve,
+        quote_reserve,
+        concentration_factor: 1,
+        fee_bps: 30,
+        ..Default::default()
+    })
+}
+
+pub struct PoolBuilder(Pool);
+
+impl PoolBuilder {
+    pub fn price(mut self, oracle_price: FixedPrice) -> Self {
+        self.0.oracle_price = oracle_price;
+        self
+    }
+
+    pub fn fee_bps(mut self, fee_bps: u16) -> Self {
+        self.0.fee_bps = fee_bps;
+        self
+    }
+
+    pub fn fee_bounds(mut self, min_fee_bps: u16, max_fee_bps: u16) -> Self {
+        self.0.set_fee_bounds(min_fee_bps, max_fee_bps).unwrap();
+        self
+    }
+
+    pub fn rebalance(mut self, target_base_bps: u16, rebalance_cap_bps: u16) -> Self {
+        self.0.set_rebalance_params(target_base_bps, rebalance_cap_bps).unwrap();
+        self
+    }
+
+    pub fn curve(mut self, curve_type: CurveType, base_spread_bps: u16) -> Self {
+        self.0.set_curve(curve_type, base_spread_bps).unwrap();
+        self
+    }
+
+    // Center the curve on the oracle price, as a rebalance would
+    pub fn recentered(mut self) -> Self {
+        self.0.recenter().unwrap();
+        self
+    }
+
+    pub fn build(self) -> Pool {
+        self.0
+    }
+}
+
+// 1,000 SOL (9 decimals) and 100,000 USDC (6 decimals) at 100 USDC per SOL, 30 bps fee,
+// recentered on a 50/50 target. Fee bounds of 0 keep the fee fixed.
+pub fn seeded_pool(rebalance_cap_bps: u16, min_fee_bps: u16, max_fee_bps: u16) -> Pool {
+    pool(1_000_000_000_000, 100_000_000_000)
+        .price(FixedPrice::from_ratio(1, 10).unwrap())
+        .rebalance(5_000, rebalance_cap_bps)
+        .fee_bounds(min_fee_bps, max_fee_bps)
+        .recentered()
+        .build()
+}
+
+pub fn curve_pool(curve_base_reserve: u64, curve_quote_reserve: u64, fee_bps: u16) -> Pool {
+    Pool {
+        curve_base_reserve,
+        curve_quote_reserve,
+        // Enough real liquidity that the curve, not the cap, sets the price
+        base_reserve: curve_base_reserve,
+        quote_reserve: curve_quote_reserve,
+        concentration_factor: 1,
+        fee_bps,
+        ..Default::default()
+    }
+}
+
+pub fn random_pool(rng: &mut Rng) -> Pool {
+    curve_pool(
+        rng.range(1_000, 1_000_000_000_000_000),
+        rng.range(1_000, 1_000_000_000_000_000),
+        rng.range(0, 1_001) as u16,
+    )
+}
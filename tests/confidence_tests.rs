@@ -0,0 +1,80 @@
+use anchor_lang::error::Error;
+use bulker_dex::error::DexError;
+use bulker_dex::fixed_point::FixedPrice;
+use bulker_dex::oracle::OraclePrice;
+use bulker_dex::pool::{Pool, SwapDirection};
+
+// Price of 100 with a confidence interval of `confidence`
+fn oracle(confidence: FixedPrice) -> OraclePrice {
+    OraclePrice { price: FixedPrice::from_int(100), confidence, twap: None }
+}
+
+// Rejects intervals over 1% of the price and quotes at one full interval from the price
+fn confidence_pool() -> Pool {
+    Pool {
+        max_confidence_bps: 100,
+        confidence_multiplier_bps: 10_000,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_wide_confidence_is_rejected() {
+    let pool = confidence_pool();
+    assert!(pool.check_confidence(&oracle(FixedPrice::from_int(1))).is_ok());
+
+    for confidence in [FixedPrice::from_ratio(102, 100).unwrap(), FixedPrice::from_int(5)] {
+        assert_eq!(
+            pool.check_confidence(&oracle(confidence)).unwrap_err(),
+            Error::from(DexError::OracleConfidenceTooWide)
+        );
+    }
+
+    // A limit of 0 disables the check
+    let pool = Pool { max_confidence_bps: 0, ..confidence_pool() };
+    assert!(pool.check_confidence(&oracle(FixedPrice::from_int(50))).is_ok());
+}
+
+#[test]
+fn test_spread_widens_against_the_trader() {
+    let pool = confidence_pool();
+    let oracle_price = oracle(FixedPrice::from_int(1));
+
+    // Sellers of base are paid at the bid of 99...
+    let out = pool.apply_confidence_spread(SwapDirection::BaseToQuote, 1_000_000, &oracle_price).unwrap();
+    assert_eq!(out, 990_000);
+
+    // ...and buyers pay the ask of 101
+    let out = pool.apply_confidence_spread(SwapDirection::QuoteToBase, 1_000_000, &oracle_price).unwrap();
+    assert_eq!(out, 990_099);
+
+    // No interval, or a multiplier of 0, quotes the price itself
+    for (pool, oracle_price) in [
+        (confidence_pool(), oracle(FixedPrice::ZERO)),
+        (Pool { confidence_multiplier_bps: 0, ..confidence_pool() }, oracle_price),
+    ] {
+        for direction in [SwapDirection::BaseToQuote, SwapDirection::QuoteToBase] {
+            assert_eq!(pool.apply_confidence_spread(direction, 1_000_000, &oracle_price).unwrap(), 1_000_000);
+        }
+    }
+}
+
+#[test]
+fn test_remove_spread_inverts_apply_spread() {
+    let pool = confidence_pool();
+    let oracle_price = oracle(FixedPrice::from_ratio(7, 10).unwrap());
+
+    for direction in [SwapDirection::BaseToQuote, SwapDirection::QuoteToBase] {
+        for amount in [1, 2, 99, 1_000, 123_457, 10_000_001, 987_654_321] {
+            // The curve output found for a wanted output pays it, and at most one atom more
+            let curve_out = pool.remove_confidence_spread(direction, amount, &oracle_price).unwrap();
+            let paid = pool.apply_confidence_spread(direction, curve_out, &oracle_price).unwrap();
+            assert!(paid >= amount && paid - amount <= 1, "{:?} {} -> {} -> {}", direction, amount, curve_out, paid);
+
+            // Round trip from the curve side loses at most one atom
+            let paid = pool.apply_confidence_spread(direction, amount, &oracle_price).unwrap();
+            let curve_out = pool.remove_confidence_spread(direction, paid, &oracle_price).unwrap();
+            assert!(curve_out <= amount && amount - curve_out <= 1, "{:?} {} -> {} -> {}", direction, amount, paid, curve_out);
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use anchor_lang::prelude::*;
+use bulker_dex::config::{check_upgrade_authority, Config, ConfigParams, MAX_ORACLE_PROGRAMS};
+use bulker_dex::error::DexError;
+use bulker_dex::pool::MAX_FEE_BPS;
+
+fn params() -> ConfigParams {
+    ConfigParams {
+        treasury: Pubkey::new_unique(),
+        protocol_fee_bps: 2_000,
+        oracle_programs: vec![Pubkey::new_unique()],
+        magic_block_authority: Pubkey::new_unique(),
+        guardian: Pubkey::new_unique(),
+        default_fee_bps: 30,
+        restrict_pool_creation: false,
+    }
+}
+
+#[test]
+fn test_apply_params_sets_fields() {
+    let mut config = Config::default();
+    let params = params();
+    config.apply_params(params.clone()).unwrap();
+    assert_eq!(config.treasury, params.treasury);
+    assert_eq!(config.protocol_fee_bps, 2_000);
+    assert_eq!(config.oracle_programs, params.oracle_programs);
+    assert_eq!(config.guardian, params.guardian);
+    assert_eq!(config.default_fee_bps, 30);
+}
+
+#[test]
+fn test_apply_params_bounds_protocol_fee() {
+    let mut config = Config::default();
+    let err = config.apply_params(ConfigParams { protocol_fee_bps: 10_001, ..params() }).unwrap_err();
+    assert_eq!(err, Error::from(DexError::InvalidFeeBps));
+
+    // The protocol may take the whole fee
+    assert!(config.apply_params(ConfigParams { protocol_fee_bps: 10_000, ..params() }).is_ok());
+}
+
+#[test]
+fn test_apply_params_bounds_default_fee() {
+    let mut config = Config::default();
+    let err = config.apply_params(ConfigParams { default_fee_bps: MAX_FEE_BPS + 1, ..params() }).unwrap_err();
+    assert_eq!(err, Error::from(DexError::InvalidFeeBps));
+    assert!(config.apply_params(ConfigParams { default_fee_bps: MAX_FEE_BPS, ..params() }).is_ok());
+}
+
+#[test]
+fn test_apply_params_bounds_oracle_programs() {
+    let mut config = Config::default();
+    let err = config.apply_params(ConfigParams { oracle_programs: vec![], ..params() }).unwrap_err();
+    assert_eq!(err, Error::from(DexError::InvalidOracleProgramList));
+
+    let too_many = (0..=MAX_ORACLE_PROGRAMS).map(|_| Pubkey::new_unique()).collect();
+    let err = config.apply_params(ConfigParams { oracle_programs: too_many, ..params() }).unwrap_err();
+    assert_eq!(err, Error::from(DexError::InvalidOracleProgramList));
+
+    let most = (0..MAX_ORACLE_PROGRAMS).map(|_| Pubkey::new_unique()).collect();
+    assert!(config.apply_params(ConfigParams { oracle_programs: most, ..params() }).is_ok());
+}
+
+#[test]
+fn test_rejected_params_leave_config_unchanged() {
+    let mut config = Config::default();
+    config.apply_params(params()).unwrap();
+    let before = config.clone();
+    assert!(config.apply_params(ConfigParams { protocol_fee_bps: 10_001, ..params() }).is_err());
+    assert_eq!(config.treasury, before.treasury);
+    assert_eq!(config.protocol_fee_bps, before.protocol_fee_bps);
+}
+
+#[test]
+fn test_admin_transfer_takes_two_steps() {
+    let admin = Pubkey::new_unique();
+    let new_admin = Pubkey::new_unique();
+    let mut config = Config { admin, ..Default::default() };
+
+    // Proposing does not hand over control
+    config.propose_admin(new_admin);
+    assert_eq!(config.admin, admin);
+    assert_eq!(config.pending_admin, new_admin);
+
+    config.accept_admin(&new_admin).unwrap();
+    assert_eq!(config.admin, new_admin);
+    assert_eq!(config.pending_admin, Pubkey::default());
+
+    // The transfer cannot be accepted twice
+    assert_eq!(config.accept_admin(&new_admin).unwrap_err(), Error::from(DexError::Unauthorized));
+}
+
+#[test]
+fn test_admin_transfer_rejects_other_keys() {
+    let admin = Pubkey::new_unique();
+    let mut config = Config { admin, ..Default::default() };
+
+    // Nothing pending: not even the default key can accept
+    assert_eq!(config.accept_admin(&Pubkey::default()).unwrap_err(), Error::from(DexError::Unauthorized));
+
+    config.propose_admin(Pubkey::new_unique());
+    let err = config.accept_admin(&Pubkey::new_unique()).unwrap_err();
+    assert_eq!(err, Error::from(DexError::Unauthorized));
+    assert_eq!(config.admin, admin);
+
+    // A new proposal replaces the old one
+    let replacement = Pubkey::new_unique();
+    config.propose_admin(replacement);
+    config.accept_admin(&replacement).unwrap();
+    assert_eq!(config.admin, replacement);
+}
+
+#[test]
+fn test_only_upgrade_authority_initializes_config() {
+    let authority = Pubkey::new_unique();
+    let program_data = ProgramData { slot: 1, upgrade_authority_address: Some(authority) };
+    assert!(check_upgrade_authority(&program_data, &authority).is_ok());
+    assert_eq!(
+        check_upgrade_authority(&program_data, &Pubkey::new_unique()).unwrap_err(),
+        Error::from(DexError::Unauthorized)
+    );
+
+    // An immutable program has no authority left to initialize it
+    let program_data = ProgramData { slot: 1, upgrade_authority_address: None };
+    assert_eq!(
+        check_upgrade_authority(&program_data, &authority).unwrap_err(),
+        Error::from(DexError::Unauthorized)
+    );
+}
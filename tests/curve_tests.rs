@@ -0,0 +1,145 @@
+mod common;
+
+use anchor_lang::error::Error;
+use bulker_dex::curve::{CurveType, MAX_BASE_SPREAD_BPS};
+use bulker_dex::error::DexError;
+use bulker_dex::fixed_point::FixedPrice;
+use bulker_dex::pool::{Pool, SwapDirection};
+
+#[test]
+fn test_small_trades_quote_mid_less_spread() {
+    let pool = common::pool(1_000_000, 100_000_000)
+        .fee_bps(0)
+        .rebalance(5_000, 100)
+        .curve(CurveType::OracleAnchored, 30)
+        .build();
+
+    // 100 base is worth 10,000 quote at the mid; 30 bps spread leaves 9,970
+    let (out, fee) = pool.compute_swap_output(SwapDirection::BaseToQuote, 100).unwrap();
+    assert_eq!(fee, 0);
+    assert!((9_968..=9_970).contains(&out), "got {}", out);
+
+    // 100,000 quote buys 1,000 base at the mid, less the spread
+    let (out, _) = pool.compute_swap_output(SwapDirection::QuoteToBase, 100_000).unwrap();
+    assert!((995..=997).contains(&out), "got {}", out);
+}
+
+#[test]
+fn test_price_impact_grows_with_size() {
+    let pool = common::pool(1_000_000, 100_000_000)
+        .fee_bps(0)
+        .rebalance(5_000, 100)
+        .curve(CurveType::OracleAnchored, 30)
+        .build();
+    let mut last_rate = u64::MAX;
+    for amount_in in [1_000, 10_000, 100_000, 1_000_000] {
+        let (out, _) = pool.compute_swap_output(SwapDirection::BaseToQuote, amount_in).unwrap();
+        // Quote received per 1,000 base sold
+        let rate = out * 1_000 / amount_in;
+        assert!(rate < last_rate, "{} base got {} quote", amount_in, out);
+        last_rate = rate;
+    }
+}
+
+#[test]
+fn test_spread_follows_inventory_skew() {
+    let pool = common::pool(1_000_000, 100_000_000)
+        .fee_bps(0)
+        .rebalance(5_000, 100)
+        .curve(CurveType::OracleAnchored, 30)
+        .build();
+    assert_eq!(pool.anchored_spread_bps(SwapDirection::BaseToQuote).unwrap(), 30);
+    assert_eq!(pool.anchored_spread_bps(SwapDirection::QuoteToBase).unwrap(), 30);
+
+    // 75% base: selling more base costs extra, buying it back is free
+    let pool = common::pool(3_000_000, 100_000_000)
+        .fee_bps(0)
+        .rebalance(5_000, 100)
+        .curve(CurveType::OracleAnchored, 30)
+        .build();
+    assert_eq!(pool.anchored_spread_bps(SwapDirection::BaseToQuote).unwrap(), 2_530);
+    assert_eq!(pool.anchored_spread_bps(SwapDirection::QuoteToBase).unwrap(), 0);
+
+    // A deeper pool leans less for the same skew
+    let mut pool = common::pool(1_000_000, 100_400_000)
+        .fee_bps(0)
+        .rebalance(5_000, 100)
+        .curve(CurveType::OracleAnchored, 30)
+        .build();
+    assert_eq!(pool.anchored_spread_bps(SwapDirection::QuoteToBase).unwrap(), 40);
+    pool.set_concentration_factor(10).unwrap();
+    assert_eq!(pool.anchored_spread_bps(SwapDirection::QuoteToBase).unwrap(), 31);
+    assert_eq!(pool.anchored_spread_bps(SwapDirection::BaseToQuote).unwrap(), 29);
+}
+
+#[test]
+fn test_swap_input_covers_anchored_output() {
+    let mut pool = common::pool(1_000_000_000, 100_000_000_000)
+        .fee_bps(0)
+        .rebalance(5_000, 100)
+        .curve(CurveType::OracleAnchored, 25)
+        .build();
+    pool.fee_bps = 30;
+    for direction in [SwapDirection::BaseToQuote, SwapDirection::QuoteToBase] {
+        for amount_out in [1, 999, 123_457, 10_000_000, 400_000_000] {
+            let (amount_in, fee) = pool.compute_swap_input(direction, amount_out).unwrap();
+            let (actual_out, actual_fee) = pool.compute_swap_output(direction, amount_in).unwrap();
+            assert!(actual_out >= amount_out, "{:?} {} -> {} -> {}", direction, amount_out, amount_in, actual_out);
+            assert_eq!(fee, actual_fee);
+        }
+    }
+}
+
+#[test]
+fn test_anchored_settlement_ignores_curve_reserves() {
+    let mut pool = common::pool(1_000_000, 100_000_000)
+        .fee_bps(0)
+        .rebalance(5_000, 100)
+        .curve(CurveType::OracleAnchored, 30)
+        .build();
+    pool.curve_base_reserve = 10;
+    pool.curve_quote_reserve = 1_000;
+
+    // The trade pays out more than the virtual curve holds but far less than the real reserve
+    let (out, fee) = pool.compute_swap_output(SwapDirection::BaseToQuote, 100).unwrap();
+    assert!(out > pool.curve_quote_reserve);
+    pool.update_reserves(SwapDirection::BaseToQuote, 100, out, fee, 0).unwrap();
+    assert_eq!(pool.base_reserve, 1_000_100);
+    assert_eq!(pool.quote_reserve, 100_000_000 - out);
+    assert_eq!((pool.curve_base_reserve, pool.curve_quote_reserve), (10, 1_000));
+
+    // Constant-product pools still track the curve they priced against
+    pool.set_curve(CurveType::ConstantProduct, 0).unwrap();
+    assert_eq!(
+        pool.update_reserves(SwapDirection::BaseToQuote, 100, out, fee, 0).unwrap_err(),
+        Error::from(DexError::InsufficientLiquidity)
+    );
+}
+
+#[test]
+fn test_anchored_pool_rebalances_on_any_move() {
+    let anchored = common::pool(1_000_000, 100_000_000)
+        .fee_bps(0)
+        .rebalance(5_000, 100)
+        .curve(CurveType::OracleAnchored, 30)
+        .build();
+    let mut constant_product = common::pool(1_000_000, 100_000_000)
+        .fee_bps(0)
+        .rebalance(5_000, 100)
+        .curve(CurveType::OracleAnchored, 30)
+        .build();
+    constant_product.set_curve(CurveType::ConstantProduct, 0).unwrap();
+
+    let moved = FixedPrice::from_int(100).mul_bps(10_050).unwrap();
+    assert!(anchored.needs_rebalance(&moved).unwrap().0);
+    assert!(!constant_product.needs_rebalance(&moved).unwrap().0);
+}
+
+#[test]
+fn test_set_curve_validates_spread() {
+    let mut pool = Pool::default();
+    assert_eq!(pool.curve_type, CurveType::ConstantProduct);
+    assert!(pool.set_curve(CurveType::OracleAnchored, MAX_BASE_SPREAD_BPS + 1).is_err());
+    assert!(pool.set_curve(CurveType::OracleAnchored, MAX_BASE_SPREAD_BPS).is_ok());
+    assert_eq!(pool.curve_type, CurveType::OracleAnchored);
+}
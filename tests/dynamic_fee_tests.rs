@@ -0,0 +1,143 @@
+mod common;
+
+use bulker_dex::fees::{SKEW_FEE_DIVISOR, VOLATILITY_FEE_DIVISOR};
+use bulker_dex::fixed_point::FixedPrice;
+use bulker_dex::oracle::OraclePrice;
+use bulker_dex::pool::{Pool, SwapDirection, MAX_FEE_BPS};
+
+// Bare oracle read, without the feed's TWAP
+fn oracle(price: FixedPrice) -> OraclePrice {
+    OraclePrice { price, confidence: FixedPrice::ZERO, twap: None }
+}
+
+#[test]
+fn test_balanced_pool_charges_base_fee() {
+    let pool = common::pool(1_000_000, 100_000_000)
+        .rebalance(5_000, 100)
+        .fee_bounds(10, 100)
+        .recentered()
+        .build();
+    assert_eq!(pool.dynamic_fee_bps(SwapDirection::BaseToQuote).unwrap(), 30);
+    assert_eq!(pool.dynamic_fee_bps(SwapDirection::QuoteToBase).unwrap(), 30);
+}
+
+#[test]
+fn test_fee_follows_inventory_skew() {
+    // 60% of value in base: 1,000 bps from target
+    let pool = common::pool(1_500_000, 100_000_000)
+        .rebalance(5_000, 100)
+        .fee_bounds(10, 100)
+        .recentered()
+        .build();
+    assert_eq!(pool.base_value_bps().unwrap(), 6_000);
+    let skew_fee_bps = 1_000 / SKEW_FEE_DIVISOR;
+    assert_eq!(pool.dynamic_fee_bps(SwapDirection::BaseToQuote).unwrap(), 30 + skew_fee_bps);
+    assert_eq!(pool.dynamic_fee_bps(SwapDirection::QuoteToBase).unwrap(), 30 - skew_fee_bps);
+
+    // 75% base: restoring trades are floored at the minimum
+    let mut pool = common::pool(3_000_000, 100_000_000)
+        .rebalance(5_000, 100)
+        .fee_bounds(10, 100)
+        .recentered()
+        .build();
+    assert_eq!(pool.dynamic_fee_bps(SwapDirection::BaseToQuote).unwrap(), 55);
+    assert_eq!(pool.dynamic_fee_bps(SwapDirection::QuoteToBase).unwrap(), 10);
+    pool.set_fee_bounds(10, 50).unwrap();
+    assert_eq!(pool.dynamic_fee_bps(SwapDirection::BaseToQuote).unwrap(), 50);
+
+    // A zero max keeps the fee flat
+    pool.set_fee_bounds(0, 0).unwrap();
+    assert_eq!(pool.dynamic_fee_bps(SwapDirection::BaseToQuote).unwrap(), 30);
+    assert_eq!(pool.dynamic_fee_bps(SwapDirection::QuoteToBase).unwrap(), 30);
+}
+
+#[test]
+fn test_swap_output_charges_dynamic_fee() {
+    let pool = common::pool(1_500_000, 100_000_000)
+        .rebalance(5_000, 100)
+        .fee_bounds(10, 100)
+        .recentered()
+        .build();
+
+    // Selling base into a base-heavy pool pays 40 bps, buying it back pays 20 bps
+    let (_, fee) = pool.compute_swap_output(SwapDirection::BaseToQuote, 10_000).unwrap();
+    assert_eq!(fee, 40);
+    let (_, fee) = pool.compute_swap_output(SwapDirection::QuoteToBase, 1_000_000).unwrap();
+    assert_eq!(fee, 2_000);
+
+    // Exact-output quotes gross up by the same fee
+    for direction in [SwapDirection::BaseToQuote, SwapDirection::QuoteToBase] {
+        let (amount_in, fee) = pool.compute_swap_input(direction, 5_000).unwrap();
+        let (amount_out, actual_fee) = pool.compute_swap_output(direction, amount_in).unwrap();
+        assert!(amount_out >= 5_000);
+        assert_eq!(fee, actual_fee);
+    }
+}
+
+#[test]
+fn test_volatility_raises_fee() {
+    let mut pool = common::pool(1_000_000, 100_000_000)
+        .rebalance(5_000, 100)
+        .fee_bounds(10, 100)
+        .recentered()
+        .build();
+    let price = pool.oracle_price;
+    let moved = price.mul_bps(10_010).unwrap();
+
+    // The first sample only sets the reference
+    pool.record_volatility(&oracle(price), 1).unwrap();
+    assert_eq!(pool.volatility_bps, 0);
+
+    // A 0.1% move over 150 slots annualizes to ~72%, a fifth of which enters the average
+    pool.record_volatility(&oracle(moved), 151).unwrap();
+    let volatility_bps = pool.volatility_bps;
+    assert!((1_440..=1_448).contains(&volatility_bps), "got {}", volatility_bps);
+    assert_eq!(pool.dynamic_fee_bps(SwapDirection::BaseToQuote).unwrap(), 30 + volatility_bps / VOLATILITY_FEE_DIVISOR);
+
+    // Further reads in the same slot are ignored
+    pool.record_volatility(&oracle(price), 151).unwrap();
+    assert_eq!(pool.volatility_bps, volatility_bps);
+
+    // A quiet market decays it back towards zero
+    for slot in 2..60 {
+        pool.record_volatility(&oracle(moved), slot * 150).unwrap();
+    }
+    assert_eq!(pool.volatility_bps, 0);
+    assert_eq!(pool.dynamic_fee_bps(SwapDirection::BaseToQuote).unwrap(), 30);
+}
+
+#[test]
+fn test_twap_gap_raises_fee() {
+    let mut pool = common::pool(1_000_000, 100_000_000)
+        .rebalance(5_000, 100)
+        .fee_bounds(10, 100)
+        .recentered()
+        .build();
+    pool.set_fee_bounds(10, 200).unwrap();
+    pool.volatility_bps = 8_000;
+    pool.record_volatility(&oracle(FixedPrice::from_int(100)), 1).unwrap();
+    assert_eq!(pool.dynamic_fee_bps(SwapDirection::BaseToQuote).unwrap(), 30 + 40);
+
+    // A price 1% above its TWAP adds half the gap
+    let feed = OraclePrice {
+        twap: Some(FixedPrice::from_int(100)),
+        ..oracle(FixedPrice::from_int(101))
+    };
+    pool.record_volatility(&feed, 1).unwrap();
+    assert_eq!(pool.twap_gap_bps, 100);
+    assert_eq!(pool.dynamic_fee_bps(SwapDirection::BaseToQuote).unwrap(), 30 + 40 + 50);
+
+    // Without a TWAP the gap clears
+    pool.record_volatility(&oracle(FixedPrice::from_int(101)), 1).unwrap();
+    assert_eq!(pool.twap_gap_bps, 0);
+    assert_eq!(pool.dynamic_fee_bps(SwapDirection::BaseToQuote).unwrap(), 30 + 40);
+}
+
+#[test]
+fn test_set_fee_bounds_validates() {
+    let mut pool = Pool::default();
+    assert!(pool.set_fee_bounds(50, 40).is_err());
+    assert!(pool.set_fee_bounds(0, MAX_FEE_BPS + 1).is_err());
+    assert!(pool.set_fee_bounds(0, MAX_FEE_BPS).is_ok());
+    assert!(pool.set_fee_bounds(0, 0).is_ok());
+}
@@ -0,0 +1,123 @@
+mod common;
+
+use bulker_dex::fixed_point::FixedPrice;
+use bulker_dex::oracle::OraclePrice;
+use bulker_dex::pool::{Pool, SwapDirection};
+use common::{seeded_pool, Rng};
+
+// One oracle update a minute
+const STEP_SLOTS: u64 = 150;
+
+// Per-update moves of the two regimes, in bps
+const CALM_STEP_BPS: u64 = 5;
+const VOLATILE_STEP_BPS: u64 = 50;
+
+// A +/- random walk: `calm_steps` small moves, then `volatile_steps` large ones
+fn price_path(calm_steps: usize, volatile_steps: usize) -> Vec<FixedPrice> {
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+    let mut price = FixedPrice::from_ratio(1, 10).unwrap();
+    (0..calm_steps + volatile_steps)
+        .map(|step| {
+            let step_bps = if step < calm_steps { CALM_STEP_BPS } else { VOLATILE_STEP_BPS };
+            let bps = if rng.next() % 2 == 0 { 10_000 + step_bps } else { 10_000 - step_bps };
+            price = price.mul_bps(bps).unwrap();
+            price
+        })
+        .collect()
+}
+
+// Trade fixed chunks against the pool while it quotes better than the oracle.
+// Returns the arbitrageur's profit at the oracle price, in quote atoms.
+fn arbitrage(pool: &mut Pool, price: &FixedPrice) -> u64 {
+    let (base_chunk, quote_chunk) = (1_000_000_000, 100_000_000);
+    let mut profit = 0;
+    loop {
+        let (out, fee) = pool.compute_swap_output(SwapDirection::BaseToQuote, base_chunk).unwrap();
+        let fair_out = price.mul_u64(base_chunk).unwrap();
+        if out > fair_out {
+            pool.update_reserves(SwapDirection::BaseToQuote, base_chunk, out, fee, 0).unwrap();
+            profit += out - fair_out;
+            continue;
+        }
+        let (out, fee) = pool.compute_swap_output(SwapDirection::QuoteToBase, quote_chunk).unwrap();
+        let out_value = price.mul_u64(out).unwrap();
+        if out_value > quote_chunk {
+            pool.update_reserves(SwapDirection::QuoteToBase, quote_chunk, out, fee, 0).unwrap();
+            profit += out_value - quote_chunk;
+            continue;
+        }
+        return profit;
+    }
+}
+
+struct Outcome {
+    lp_pnl: i128,           // Pool value less the value of holding the seed, at the final price
+    arbitrage_profit: u64,  // What arbitrageurs extracted from stale quotes
+    fee_bps: Vec<u64>,      // Fee quoted after each step
+}
+
+// Replays `path` against `pool`: each step reads the oracle, rebalances if needed, lets
+// arbitrageurs trade the pool back to the oracle, then fills one uninformed order.
+fn simulate(mut pool: Pool, path: &[FixedPrice]) -> Outcome {
+    let seed = (pool.base_reserve, pool.quote_reserve);
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    let mut arbitrage_profit = 0;
+    let mut fee_bps = Vec::with_capacity(path.len());
+
+    for (step, price) in path.iter().enumerate() {
+        let oracle_price = OraclePrice { price: *price, confidence: FixedPrice::ZERO, twap: None };
+        pool.record_volatility(&oracle_price, (step as u64 + 1) * STEP_SLOTS).unwrap();
+        if pool.needs_rebalance(price).unwrap().0 {
+            pool.rebalance(*price).unwrap();
+        }
+
+        arbitrage_profit += arbitrage(&mut pool, price);
+
+        // Uninformed flow trades regardless of the fee
+        let (direction, amount_in) = if rng.next() % 2 == 0 {
+            (SwapDirection::BaseToQuote, 2_000_000_000)
+        } else {
+            (SwapDirection::QuoteToBase, 200_000_000)
+        };
+        let (out, fee) = pool.compute_swap_output(direction, amount_in).unwrap();
+        pool.update_reserves(direction, amount_in, out, fee, 0).unwrap();
+
+        fee_bps.push(pool.dynamic_fee_bps(SwapDirection::BaseToQuote).unwrap());
+    }
+
+    let final_price = path.last().unwrap();
+    let value = |base: u64, quote: u64| final_price.mul_u64(base).unwrap() as i128 + quote as i128;
+    Outcome {
+        lp_pnl: value(pool.base_reserve, pool.quote_reserve) - value(seed.0, seed.1),
+        arbitrage_profit,
+        fee_bps,
+    }
+}
+
+fn average(fees: &[u64]) -> u64 {
+    fees.iter().sum::<u64>() / fees.len() as u64
+}
+
+#[test]
+fn test_volatility_fee_tracks_market() {
+    let path = price_path(500, 500);
+    let fixed = simulate(seeded_pool(100, 0, 0), &path);
+    assert!(fixed.fee_bps.iter().all(|&fee| fee == 30));
+
+    let dynamic = simulate(seeded_pool(100, 10, 300), &path);
+    let (calm_fee, volatile_fee) = (average(&dynamic.fee_bps[..500]), average(&dynamic.fee_bps[500..]));
+    assert!(volatile_fee > 3 * calm_fee, "calm {} bps, volatile {} bps", calm_fee, volatile_fee);
+}
+
+#[test]
+fn test_volatility_fee_improves_lp_pnl() {
+    let path = price_path(500, 500);
+    let fixed = simulate(seeded_pool(100, 0, 0), &path);
+    let dynamic = simulate(seeded_pool(100, 10, 300), &path);
+
+    // Less is lost to arbitrageurs and more is earned from uninformed flow
+    assert!(dynamic.arbitrage_profit < fixed.arbitrage_profit,
+        "arbitrage took {} vs {} with a fixed fee", dynamic.arbitrage_profit, fixed.arbitrage_profit);
+    assert!(dynamic.lp_pnl > fixed.lp_pnl,
+        "LP PnL {} vs {} with a fixed fee", dynamic.lp_pnl, fixed.lp_pnl);
+}
@@ -0,0 +1,60 @@
+use bulker_dex::fixed_point::FixedPrice;
+
+#[test]
+fn test_from_decimal_matches_pyth_exponent() {
+    // 101.25 with a Pyth exponent of -8
+    let price = FixedPrice::from_decimal(10_125_000_000, -8).unwrap();
+    assert_eq!(price, FixedPrice::from_ratio(405, 4).unwrap());
+    assert_eq!(price.to_string(), "101.250000");
+
+    let price = FixedPrice::from_decimal(3, 2).unwrap();
+    assert_eq!(price, FixedPrice::from_int(300));
+}
+
+#[test]
+fn test_mul_and_div_round_down() {
+    let price = FixedPrice::from_ratio(1, 3).unwrap();
+    assert_eq!(price.mul_u64(10).unwrap(), 3);
+    assert_eq!(price.mul_u64_ceil(10).unwrap(), 4);
+
+    let price = FixedPrice::from_int(100);
+    assert_eq!(price.mul_u64(1_000_000_000).unwrap(), 100_000_000_000);
+    assert_eq!(price.mul_u64_ceil(1_000_000_000).unwrap(), 100_000_000_000);
+    assert_eq!(price.div_u64(250).unwrap(), 2);
+}
+
+#[test]
+fn test_change_bps() {
+    let old = FixedPrice::from_int(100);
+    assert_eq!(old.change_bps(&FixedPrice::from_int(101)).unwrap(), 100);
+    assert_eq!(old.change_bps(&FixedPrice::from_int(95)).unwrap(), 500);
+    assert!(FixedPrice::ZERO.change_bps(&old).is_err());
+}
+
+#[test]
+fn test_overflow_is_reported() {
+    assert!(FixedPrice::from_int(u64::MAX).mul_u64(2).is_err());
+    assert!(FixedPrice::from_decimal(u64::MAX, 1).is_err());
+    assert!(FixedPrice::ZERO.div_u64(1).is_err());
+}
+
+#[test]
+fn test_mul_ratio_preserves_precision_for_large_prices() {
+    let bid = FixedPrice::from_ratio(399, 4).unwrap(); // 99.75
+    let price = FixedPrice::from_int(100);
+    assert_eq!(FixedPrice::mul_ratio_u64(1_000_000, &bid, &price).unwrap(), 997_500);
+    assert_eq!(FixedPrice::mul_ratio_u64(1_000_000, &price, &price).unwrap(), 1_000_000);
+    assert!(FixedPrice::mul_ratio_u64(1, &price, &FixedPrice::ZERO).is_err());
+}
+
+#[test]
+fn test_mul_ratio_ceil_inverts_mul_ratio() {
+    let bid = FixedPrice::from_ratio(399, 4).unwrap(); // 99.75
+    let price = FixedPrice::from_int(100);
+    assert_eq!(FixedPrice::mul_ratio_u64_ceil(997_500, &price, &bid).unwrap(), 1_000_000);
+    assert_eq!(FixedPrice::mul_ratio_u64_ceil(997_501, &price, &bid).unwrap(), 1_000_002);
+    for amount in [1, 7, 997_501, 123_456_789] {
+        let gross = FixedPrice::mul_ratio_u64_ceil(amount, &price, &bid).unwrap();
+        assert!(FixedPrice::mul_ratio_u64(gross, &bid, &price).unwrap() >= amount);
+    }
+}
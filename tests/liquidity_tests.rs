@@ -0,0 +1,208 @@
+mod common;
+
+use anchor_lang::error::Error;
+use bulker_dex::error::DexError;
+use bulker_dex::fixed_point::FixedPrice;
+use bulker_dex::pool::SwapDirection;
+
+// 200,000,000 quote atoms of value held by 200,000,000 shares
+const LP_SUPPLY: u64 = 200_000_000;
+
+#[test]
+fn test_first_deposit_mints_value() {
+    let pool = common::pool(0, 0).build();
+    assert_eq!(pool.compute_lp_shares(1_000_000, 100_000_000, 0).unwrap(), 200_000_000);
+
+    // Any ratio is accepted for the first deposit: there is no pool to trade against
+    assert_eq!(pool.compute_lp_shares(0, 5_000, 0).unwrap(), 5_000);
+}
+
+#[test]
+fn test_proportional_deposit_pays_no_fee() {
+    let pool = common::pool(1_000_000, 100_000_000).build();
+    assert_eq!(pool.imbalance_fee_value(10_000, 1_000_000).unwrap(), 0);
+    assert_eq!(pool.compute_lp_shares(10_000, 1_000_000, LP_SUPPLY).unwrap(), 2_000_000);
+}
+
+#[test]
+fn test_single_sided_deposit_pays_swap_fee() {
+    let pool = common::pool(1_000_000, 100_000_000).build();
+
+    // 20,000 base is worth 2,000,000 quote; all of it is excess and pays 60 base of fee
+    assert_eq!(pool.imbalance_fee_value(20_000, 0).unwrap(), 6_000);
+    assert_eq!(pool.compute_lp_shares(20_000, 0, LP_SUPPLY).unwrap(), 1_994_000);
+
+    // Quote-only deposits pay the same
+    assert_eq!(pool.imbalance_fee_value(0, 2_000_000).unwrap(), 6_000);
+    assert_eq!(pool.compute_lp_shares(0, 2_000_000, LP_SUPPLY).unwrap(), 1_994_000);
+
+    // Only the part beyond the reserve ratio is charged: 1,000,000 of the quote is excess
+    assert_eq!(pool.compute_lp_shares(10_000, 2_000_000, LP_SUPPLY).unwrap(), 2_997_000);
+}
+
+#[test]
+fn test_deposit_into_one_sided_pool() {
+    // A pool holding only quote treats any base as excess, and vice versa
+    let pool = common::pool(0, 100_000_000).build();
+    assert_eq!(pool.imbalance_fee_value(10_000, 0).unwrap(), 3_000);
+    assert_eq!(pool.imbalance_fee_value(0, 1_000_000).unwrap(), 0);
+
+    let pool = common::pool(1_000_000, 0).build();
+    assert_eq!(pool.imbalance_fee_value(0, 1_000_000).unwrap(), 3_000);
+    assert_eq!(pool.imbalance_fee_value(10_000, 0).unwrap(), 0);
+}
+
+#[test]
+fn test_deposit_rounding_never_favours_depositor() {
+    let amounts = [0, 1, 7, 99, 1_001, 33_333, 1_000_003, 77_777_777];
+    for (base_reserve, quote_reserve, lp_supply) in [
+        (1_000_000, 100_000_000, LP_SUPPLY),
+        (3, 1_000_000_007, 999_999),
+        (123_456_789, 17, 1_000),
+    ] {
+        let pool = common::pool(base_reserve, quote_reserve).build();
+        let pool_value = pool.value_in_quote(base_reserve, quote_reserve).unwrap();
+        for base_amount in amounts {
+            for quote_amount in amounts {
+                let deposit_value = pool.value_in_quote(base_amount, quote_amount).unwrap();
+                let shares = pool.compute_lp_shares(base_amount, quote_amount, lp_supply).unwrap();
+
+                // The new shares are worth no more than what was paid in...
+                let value_after = pool
+                    .value_in_quote(base_reserve + base_amount, quote_reserve + quote_amount)
+                    .unwrap();
+                let supply_after = (lp_supply + shares) as u128;
+                assert!(shares as u128 * value_after <= deposit_value * supply_after,
+                    "{} base + {} quote minted {} shares", base_amount, quote_amount, shares);
+
+                // ...so existing shares are worth no less than before
+                assert!(value_after * lp_supply as u128 >= pool_value * supply_after);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_deposit_is_priced_at_the_refreshed_price() {
+    // The oracle fell 50 bps, too little to trigger a rebalance
+    let mut pool = common::pool(1_000_000, 100_000_000).build();
+    let price = FixedPrice::from_ratio(995, 10).unwrap();
+    assert!(!pool.needs_rebalance(&price).unwrap().0);
+    let stale_shares = pool.compute_lp_shares(20_000, 0, LP_SUPPLY).unwrap();
+    assert_eq!(stale_shares, 1_994_000);
+
+    // Marked to the live price, the base buys fewer shares
+    pool.rebalance(price).unwrap();
+    let shares = pool.compute_lp_shares(20_000, 0, LP_SUPPLY).unwrap();
+    assert_eq!(shares, 1_989_002);
+
+    // Withdrawing straight away returns less than was paid in, at the live price
+    pool.base_reserve += 20_000;
+    let (base_out, quote_out) = pool.compute_withdrawal(shares, LP_SUPPLY + shares).unwrap();
+    assert_eq!((base_out, quote_out), (10_044, 984_708));
+    assert!(pool.value_in_quote(base_out, quote_out).unwrap() < pool.value_in_quote(20_000, 0).unwrap());
+}
+
+#[test]
+fn test_withdrawal_is_pro_rata() {
+    let pool = common::pool(1_000_000, 100_000_000).build();
+    assert_eq!(pool.compute_withdrawal(20_000_000, LP_SUPPLY).unwrap(), (100_000, 10_000_000));
+
+    // Rounded down for the withdrawer
+    assert_eq!(pool.compute_withdrawal(1, LP_SUPPLY).unwrap(), (0, 0));
+    assert_eq!(pool.compute_withdrawal(3_000_001, LP_SUPPLY).unwrap(), (15_000, 1_500_000));
+}
+
+#[test]
+fn test_full_withdrawal_empties_reserves() {
+    let pool = common::pool(1_000_003, 99_999_999).build();
+    assert_eq!(pool.compute_withdrawal(LP_SUPPLY, LP_SUPPLY).unwrap(), (1_000_003, 99_999_999));
+
+    let err = pool.compute_withdrawal(LP_SUPPLY + 1, LP_SUPPLY).unwrap_err();
+    assert_eq!(err, Error::from(DexError::InsufficientLiquidity));
+}
+
+#[test]
+fn test_withdrawal_without_supply_fails() {
+    let pool = common::pool(1_000_000, 100_000_000).build();
+    assert_eq!(pool.compute_withdrawal(0, 0).unwrap_err(), Error::from(DexError::InsufficientLiquidity));
+    assert_eq!(pool.compute_withdrawal(1, 0).unwrap_err(), Error::from(DexError::InsufficientLiquidity));
+}
+
+#[test]
+fn test_withdrawal_excludes_protocol_fees() {
+    let mut pool = common::pool(1_000_000, 100_000_000).build();
+    pool.protocol_fees_base = 5_000;
+    pool.protocol_fees_quote = 700_000;
+
+    // The vaults hold the fees on top of the reserves; LPs only redeem the reserves
+    let (base_out, quote_out) = pool.compute_withdrawal(LP_SUPPLY, LP_SUPPLY).unwrap();
+    assert_eq!((base_out, quote_out), (1_000_000, 100_000_000));
+    pool.base_reserve -= base_out;
+    pool.quote_reserve -= quote_out;
+    assert!(pool.check_vault_invariant(5_000, 700_000).is_ok());
+}
+
+#[test]
+fn test_vault_surplus_is_paid_to_lps() {
+    let mut pool = common::pool(1_000_000, 100_000_000).build();
+    pool.protocol_fees_quote = 700_000;
+
+    // A donation of 1,000 base and 3 quote of rounding dust sit in the vaults
+    pool.sync_reserves(1_001_000, 100_700_003).unwrap();
+    assert_eq!((pool.base_reserve, pool.quote_reserve), (1_001_000, 100_000_003));
+    assert_eq!(pool.compute_withdrawal(LP_SUPPLY, LP_SUPPLY).unwrap(), (1_001_000, 100_000_003));
+    assert_eq!(pool.protocol_fees_quote, 700_000);
+
+    // Vaults short of the accounted balances are refused rather than synced down
+    assert_eq!(
+        pool.sync_reserves(1_000_000, 100_700_003).unwrap_err(),
+        Error::from(DexError::VaultBelowReserves)
+    );
+}
+
+#[test]
+fn test_single_sided_round_trip_is_not_a_free_swap() {
+    let mut pool = common::pool(1_000_000, 100_000_000).build();
+    let deposit_value = pool.value_in_quote(20_000, 0).unwrap();
+    let fee_value = pool.imbalance_fee_value(20_000, 0).unwrap();
+
+    let shares = pool.compute_lp_shares(20_000, 0, LP_SUPPLY).unwrap();
+    pool.base_reserve += 20_000;
+    let supply = LP_SUPPLY + shares;
+    let (base_out, quote_out) = pool.compute_withdrawal(shares, supply).unwrap();
+    assert!(quote_out > 0);
+
+    // Swapping half the base to quote this way costs the swap fee, less the depositor's
+    // own share of it while they held the position
+    let withdrawn_value = pool.value_in_quote(base_out, quote_out).unwrap();
+    let fee_kept_by_others = fee_value * LP_SUPPLY as u128 / supply as u128;
+    assert!(withdrawn_value + fee_kept_by_others <= deposit_value,
+        "withdrew {} of {}", withdrawn_value, deposit_value);
+}
+
+#[test]
+fn test_swap_fees_stay_with_lps_and_protocol_fees_do_not() {
+    let mut pool = common::pool(1_000_000, 100_000_000).build();
+    pool.set_rebalance_params(5_000, 0).unwrap();
+    pool.recenter().unwrap();
+    let (mut base_vault, mut quote_vault) = (pool.base_reserve, pool.quote_reserve);
+
+    // 20% of the 30 bps fee on 10,000 base goes to the protocol
+    let (out, fee) = pool.compute_swap_output(SwapDirection::BaseToQuote, 10_000).unwrap();
+    assert_eq!(fee, 30);
+    pool.update_reserves(SwapDirection::BaseToQuote, 10_000, out, fee, 2_000).unwrap();
+    base_vault += 10_000;
+    quote_vault -= out;
+    assert_eq!(pool.fees_collected_base, 30);
+    assert_eq!(pool.protocol_fees_base, 6);
+
+    // The LP share of the fee is in the reserves, the protocol share is not
+    assert_eq!(pool.base_reserve, 1_000_000 + 10_000 - 6);
+    assert_eq!(base_vault - pool.base_reserve, pool.protocol_fees_base);
+
+    // Redeeming every share leaves exactly the protocol fees behind in the vaults
+    let (base_out, quote_out) = pool.compute_withdrawal(LP_SUPPLY, LP_SUPPLY).unwrap();
+    assert_eq!((base_out, quote_out), (pool.base_reserve, pool.quote_reserve));
+    assert_eq!((base_vault - base_out, quote_vault - quote_out), (6, 0));
+}
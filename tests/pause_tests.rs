@@ -0,0 +1,122 @@
+use anchor_lang::error::Error;
+use bulker_dex::config::{PAUSE_ALL, PAUSE_DELEGATION, PAUSE_DEPOSIT, PAUSE_SWAP, PAUSE_WITHDRAW};
+use bulker_dex::error::DexError;
+use bulker_dex::fixed_point::FixedPrice;
+use bulker_dex::pool::Pool;
+
+const OPERATIONS: [u8; 4] = [PAUSE_SWAP, PAUSE_DEPOSIT, PAUSE_WITHDRAW, PAUSE_DELEGATION];
+
+fn assert_paused(pool: &Pool, global_paused: u8, operation: u8) {
+    assert_eq!(
+        pool.check_not_paused(global_paused, operation).unwrap_err(),
+        Error::from(DexError::PoolPaused)
+    );
+}
+
+// Pool with a 5% breaker over 100 slots, its window opened at 100 quote per base in slot 1
+fn breaker_pool() -> Pool {
+    let mut pool = Pool::default();
+    pool.set_circuit_breaker(500, 100);
+    assert!(!pool.check_circuit_breaker(FixedPrice::from_int(100), 1).unwrap());
+    pool
+}
+
+#[test]
+fn test_each_pool_pause_bit_blocks_only_its_operation() {
+    for flag in OPERATIONS {
+        let mut pool = Pool::default();
+        pool.set_paused(flag).unwrap();
+        for operation in OPERATIONS {
+            if operation == flag {
+                assert_paused(&pool, 0, operation);
+            } else {
+                assert!(pool.check_not_paused(0, operation).is_ok());
+            }
+        }
+    }
+}
+
+#[test]
+fn test_each_global_pause_bit_blocks_every_pool() {
+    let pool = Pool::default();
+    for flag in OPERATIONS {
+        for operation in OPERATIONS {
+            if operation == flag {
+                assert_paused(&pool, flag, operation);
+            } else {
+                assert!(pool.check_not_paused(flag, operation).is_ok());
+            }
+        }
+    }
+
+    // The pool and global flags combine
+    let mut pool = Pool::default();
+    pool.set_paused(PAUSE_SWAP).unwrap();
+    assert_paused(&pool, PAUSE_DEPOSIT, PAUSE_SWAP);
+    assert_paused(&pool, PAUSE_DEPOSIT, PAUSE_DEPOSIT);
+    assert!(pool.check_not_paused(PAUSE_DEPOSIT, PAUSE_WITHDRAW).is_ok());
+
+    for operation in OPERATIONS {
+        assert_paused(&Pool::default(), PAUSE_ALL, operation);
+    }
+}
+
+#[test]
+fn test_unknown_pause_bits_are_rejected() {
+    let mut pool = Pool::default();
+    assert_eq!(pool.set_paused(PAUSE_ALL + 1).unwrap_err(), Error::from(DexError::InvalidPauseFlags));
+    assert_eq!(pool.paused, 0);
+}
+
+#[test]
+fn test_breaker_trips_on_large_move() {
+    let mut pool = breaker_pool();
+
+    // 4% is within the threshold
+    assert!(!pool.check_circuit_breaker(FixedPrice::from_int(104), 50).unwrap());
+    assert!(pool.check_not_paused(0, PAUSE_SWAP).is_ok());
+
+    // 6% within the window trips it and pauses swaps only
+    assert!(pool.check_circuit_breaker(FixedPrice::from_int(94), 60).unwrap());
+    assert_paused(&pool, 0, PAUSE_SWAP);
+    assert!(pool.check_not_paused(0, PAUSE_WITHDRAW).is_ok());
+}
+
+#[test]
+fn test_breaker_window_expires() {
+    let mut pool = breaker_pool();
+
+    // The same move spread over more than the window opens a new window instead
+    assert!(!pool.check_circuit_breaker(FixedPrice::from_int(110), 102).unwrap());
+    assert_eq!(pool.breaker_ref_price, FixedPrice::from_int(110));
+    assert_eq!(pool.breaker_ref_slot, 102);
+    assert!(pool.check_not_paused(0, PAUSE_SWAP).is_ok());
+}
+
+#[test]
+fn test_breaker_reset_by_unpausing() {
+    let mut pool = breaker_pool();
+    assert!(pool.check_circuit_breaker(FixedPrice::from_int(90), 10).unwrap());
+
+    // Resuming swaps clears the reference, so the new price opens a fresh window
+    pool.set_paused(0).unwrap();
+    assert!(pool.breaker_ref_price.is_zero());
+    assert!(!pool.check_circuit_breaker(FixedPrice::from_int(90), 11).unwrap());
+    assert!(pool.check_not_paused(0, PAUSE_SWAP).is_ok());
+
+    // Keeping swaps paused while changing other flags keeps the window
+    assert!(pool.check_circuit_breaker(FixedPrice::from_int(80), 12).unwrap());
+    pool.set_paused(PAUSE_SWAP | PAUSE_DEPOSIT).unwrap();
+    assert_eq!(pool.breaker_ref_price, FixedPrice::from_int(90));
+}
+
+#[test]
+fn test_reconfiguring_breaker_does_not_unpause() {
+    let mut pool = breaker_pool();
+    assert!(pool.check_circuit_breaker(FixedPrice::from_int(90), 10).unwrap());
+
+    // The pool authority can disable the breaker, but only the guardian lifts the pause
+    pool.set_circuit_breaker(0, 0);
+    assert_paused(&pool, 0, PAUSE_SWAP);
+    assert!(!pool.check_circuit_breaker(FixedPrice::from_int(50), 20).unwrap());
+}
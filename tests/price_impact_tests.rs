@@ -0,0 +1,27 @@
+use bulker_dex::fixed_point::FixedPrice;
+use bulker_dex::pool::{price_impact_bps, SwapDirection};
+
+#[test]
+fn test_price_impact_base_to_quote() {
+    let price = FixedPrice::from_int(100);
+    // 10 base buys 1_000 quote at the oracle price
+    assert_eq!(price_impact_bps(&price, SwapDirection::BaseToQuote, 10, 1_000).unwrap(), 0);
+    assert_eq!(price_impact_bps(&price, SwapDirection::BaseToQuote, 10, 990).unwrap(), 100);
+    assert_eq!(price_impact_bps(&price, SwapDirection::BaseToQuote, 10, 500).unwrap(), 5_000);
+}
+
+#[test]
+fn test_price_impact_quote_to_base() {
+    let price = FixedPrice::from_int(100);
+    // 1_000 quote buys 10 base at the oracle price
+    assert_eq!(price_impact_bps(&price, SwapDirection::QuoteToBase, 1_000, 10).unwrap(), 0);
+    assert_eq!(price_impact_bps(&price, SwapDirection::QuoteToBase, 1_000, 9).unwrap(), 1_000);
+}
+
+#[test]
+fn test_price_improvement_is_not_impact() {
+    let price = FixedPrice::from_int(100);
+    assert_eq!(price_impact_bps(&price, SwapDirection::BaseToQuote, 10, 1_010).unwrap(), 0);
+    // Inputs too small to be worth an atom at the oracle price have no measurable impact
+    assert_eq!(price_impact_bps(&price, SwapDirection::QuoteToBase, 50, 0).unwrap(), 0);
+}
@@ -0,0 +1,208 @@
+use anchor_lang::error::Error;
+use anchor_lang::prelude::{AccountInfo, Pubkey};
+use anchor_lang::solana_program::instruction::BorrowedInstruction;
+use anchor_lang::solana_program::{ed25519_program, system_program, sysvar};
+use bulker_dex::config::{Config, MAX_TRUSTED_SIGNERS};
+use bulker_dex::error::DexError;
+use bulker_dex::fixed_point::FixedPrice;
+use bulker_dex::pyth_parser::{
+    get_lazer_payload, parse_lazer_envelope, verify_lazer_message, LAZER_PAYLOAD_FORMAT_MAGIC,
+    LAZER_PROPERTY_CONFIDENCE, LAZER_PROPERTY_EXPONENT, LAZER_PROPERTY_PRICE,
+    LAZER_SOLANA_FORMAT_MAGIC,
+};
+use ed25519_dalek::{Signer, SigningKey};
+
+const SOL_USD_FEED_ID: u32 = 6;
+const TIMESTAMP_US: u64 = 1_700_000_000_000_000;
+
+fn signing_key(seed: u8) -> SigningKey {
+    SigningKey::from_bytes(&[seed; 32])
+}
+
+fn signer_pubkey(key: &SigningKey) -> Pubkey {
+    Pubkey::new_from_array(key.verifying_key().to_bytes())
+}
+
+// Payload with a single feed: price 150.25 (expo -8), confidence 0.05
+fn build_payload() -> Vec<u8> {
+    let mut payload = Vec::new();
+    payload.extend_from_slice(&LAZER_PAYLOAD_FORMAT_MAGIC.to_le_bytes());
+    payload.extend_from_slice(&TIMESTAMP_US.to_le_bytes());
+    payload.push(1); // channel
+    payload.push(1); // feeds
+    payload.extend_from_slice(&SOL_USD_FEED_ID.to_le_bytes());
+    payload.push(3); // properties
+    payload.push(LAZER_PROPERTY_PRICE);
+    payload.extend_from_slice(&15_025_000_000i64.to_le_bytes());
+    payload.push(LAZER_PROPERTY_EXPONENT);
+    payload.extend_from_slice(&(-8i16).to_le_bytes());
+    payload.push(LAZER_PROPERTY_CONFIDENCE);
+    payload.extend_from_slice(&5_000_000i64.to_le_bytes());
+    payload
+}
+
+fn build_message(key: &SigningKey, payload: &[u8]) -> Vec<u8> {
+    let signature = key.sign(payload).to_bytes();
+    let mut message = Vec::new();
+    message.extend_from_slice(&LAZER_SOLANA_FORMAT_MAGIC.to_le_bytes());
+    message.extend_from_slice(&signature);
+    message.extend_from_slice(&key.verifying_key().to_bytes());
+    message.extend_from_slice(&(payload.len() as u16).to_le_bytes());
+    message.extend_from_slice(payload);
+    message
+}
+
+// Same layout as the Ed25519 program's `new_ed25519_instruction`
+fn build_ed25519_ix_data(key: &SigningKey, payload: &[u8]) -> Vec<u8> {
+    let signature = key.sign(payload).to_bytes();
+    let public_key_offset: u16 = 16;
+    let signature_offset: u16 = public_key_offset + 32;
+    let message_offset: u16 = signature_offset + 64;
+
+    let mut data = vec![1u8, 0u8];
+    for value in [
+        signature_offset,
+        u16::MAX,
+        public_key_offset,
+        u16::MAX,
+        message_offset,
+        payload.len() as u16,
+        u16::MAX,
+    ] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    data.extend_from_slice(&key.verifying_key().to_bytes());
+    data.extend_from_slice(&signature);
+    data.extend_from_slice(payload);
+    data
+}
+
+// Instructions sysvar data for a transaction whose first instruction is `ed25519_ix_data`
+fn instructions_sysvar_data(ed25519_ix_data: &[u8]) -> Vec<u8> {
+    sysvar::instructions::construct_instructions_data(&[BorrowedInstruction {
+        program_id: &ed25519_program::ID,
+        accounts: vec![],
+        data: ed25519_ix_data,
+    }])
+}
+
+#[test]
+fn test_verify_and_decode_lazer_message() {
+    let key = signing_key(7);
+    let payload = build_payload();
+    let message = build_message(&key, &payload);
+    let ix_data = build_ed25519_ix_data(&key, &payload);
+
+    let envelope = parse_lazer_envelope(&message).unwrap();
+    assert_eq!(envelope.signer, signer_pubkey(&key));
+    assert_eq!(envelope.payload, &payload[..]);
+
+    let decoded = verify_lazer_message(&ix_data, &message, &[signer_pubkey(&key)]).unwrap();
+    assert_eq!(decoded.timestamp_us, TIMESTAMP_US);
+    assert_eq!(decoded.feeds.len(), 1);
+
+    let price = decoded.get_price(SOL_USD_FEED_ID).unwrap();
+    assert_eq!(price.price, FixedPrice::from_ratio(601, 4).unwrap());
+    assert_eq!(price.confidence, FixedPrice::from_ratio(1, 20).unwrap());
+    assert_eq!(price.timestamp_us, TIMESTAMP_US);
+
+    assert!(decoded.get_price(SOL_USD_FEED_ID + 1).is_err());
+}
+
+#[test]
+fn test_rejects_untrusted_signer() {
+    let key = signing_key(7);
+    let payload = build_payload();
+    let message = build_message(&key, &payload);
+    let ix_data = build_ed25519_ix_data(&key, &payload);
+
+    let trusted = [signer_pubkey(&signing_key(8))];
+    assert!(verify_lazer_message(&ix_data, &message, &trusted).is_err());
+}
+
+#[test]
+fn test_rejects_payload_not_covered_by_signature_instruction() {
+    let key = signing_key(7);
+    let payload = build_payload();
+    let ix_data = build_ed25519_ix_data(&key, &payload);
+
+    // Tamper with the price after signing
+    let mut tampered = payload.clone();
+    let price_offset = tampered.len() - 8 - 1 - 2 - 1 - 8;
+    tampered[price_offset] ^= 0xff;
+    let message = build_message(&key, &tampered);
+
+    assert!(verify_lazer_message(&ix_data, &message, &[signer_pubkey(&key)]).is_err());
+}
+
+#[test]
+fn test_rejects_signature_instruction_referencing_other_instruction() {
+    let key = signing_key(7);
+    let payload = build_payload();
+    let message = build_message(&key, &payload);
+    let mut ix_data = build_ed25519_ix_data(&key, &payload);
+
+    // message_instruction_index -> instruction 0
+    ix_data[14..16].copy_from_slice(&0u16.to_le_bytes());
+    assert!(verify_lazer_message(&ix_data, &message, &[signer_pubkey(&key)]).is_err());
+}
+
+#[test]
+fn test_rejects_malformed_messages() {
+    let key = signing_key(7);
+    let payload = build_payload();
+    let ix_data = build_ed25519_ix_data(&key, &payload);
+    let trusted = [signer_pubkey(&key)];
+
+    let mut bad_magic = build_message(&key, &payload);
+    bad_magic[0] ^= 0xff;
+    assert!(verify_lazer_message(&ix_data, &bad_magic, &trusted).is_err());
+
+    let mut truncated = build_message(&key, &payload);
+    truncated.pop();
+    assert!(verify_lazer_message(&ix_data, &truncated, &trusted).is_err());
+}
+
+#[test]
+fn test_config_trusted_signers_gate_lazer_prices() {
+    let key = signing_key(7);
+    let payload = build_payload();
+    let message = build_message(&key, &payload);
+    let mut data = instructions_sysvar_data(&build_ed25519_ix_data(&key, &payload));
+    let mut lamports = 1_000_000;
+    let sysvar_key = sysvar::instructions::ID;
+    let instructions_sysvar = AccountInfo::new(
+        &sysvar_key, false, false, &mut lamports, &mut data, &system_program::ID, false, 0,
+    );
+
+    // A config that trusts someone else rejects the message
+    let mut config = Config::default();
+    config.set_trusted_signers(vec![signer_pubkey(&signing_key(8))]).unwrap();
+    let err = get_lazer_payload(&instructions_sysvar, 0, &message, &config.trusted_signers).unwrap_err();
+    assert_eq!(err, Error::from(DexError::UntrustedOracleSigner));
+
+    // As does one with Lazer disabled
+    config.set_trusted_signers(vec![]).unwrap();
+    let err = get_lazer_payload(&instructions_sysvar, 0, &message, &config.trusted_signers).unwrap_err();
+    assert_eq!(err, Error::from(DexError::UntrustedOracleSigner));
+
+    config.set_trusted_signers(vec![signer_pubkey(&key)]).unwrap();
+    let decoded = get_lazer_payload(&instructions_sysvar, 0, &message, &config.trusted_signers).unwrap();
+    assert_eq!(decoded.get_price(SOL_USD_FEED_ID).unwrap().price, FixedPrice::from_ratio(601, 4).unwrap());
+
+    // The signature must come from an Ed25519 instruction at the given index
+    let err = get_lazer_payload(&instructions_sysvar, 1, &message, &config.trusted_signers).unwrap_err();
+    assert_eq!(err, Error::from(DexError::InvalidSignatureInstruction));
+}
+
+#[test]
+fn test_trusted_signer_list_validates() {
+    let mut config = Config::default();
+    let too_many = (0..=MAX_TRUSTED_SIGNERS).map(|_| Pubkey::new_unique()).collect();
+    let err = config.set_trusted_signers(too_many).unwrap_err();
+    assert_eq!(err, Error::from(DexError::InvalidTrustedSignerList));
+
+    let err = config.set_trusted_signers(vec![Pubkey::default()]).unwrap_err();
+    assert_eq!(err, Error::from(DexError::InvalidTrustedSignerList));
+    assert!(config.trusted_signers.is_empty());
+}
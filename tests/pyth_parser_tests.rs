@@ -0,0 +1,193 @@
+use anchor_lang::error::{Error, ErrorCode};
+use anchor_lang::prelude::{AccountInfo, Clock, Pubkey};
+use bulker_dex::error::DexError;
+use bulker_dex::fixed_point::FixedPrice;
+use bulker_dex::pyth_parser::{
+    get_pyth_price, parse_pyth_price, Ema, PriceInfo, PriceMsg, PYTH_ACCOUNT_TYPE_PRICE, PYTH_MAGIC,
+    PYTH_PRICE_TYPE_PRICE, PYTH_STATUS_TRADING, PYTH_VERSION_2,
+};
+use bytemuck::Zeroable;
+
+fn valid_price_msg() -> PriceMsg {
+    PriceMsg {
+        magic: PYTH_MAGIC,
+        ver: PYTH_VERSION_2,
+        atype: PYTH_ACCOUNT_TYPE_PRICE,
+        size: PRICE_ACCOUNT_SIZE as u32,
+        price_type: PYTH_PRICE_TYPE_PRICE,
+        expo: -8,
+        num: 5,
+        num_qt: 5,
+        last_slot: 1_000,
+        valid_slot: 999,
+        ema_price: Ema { val: 10_000_000_000, numer: 0, denom: 0 },
+        ema_conf: Ema::zeroed(),
+        timestamp: 1_700_000_000,
+        min_pub: 3,
+        drv2: 0,
+        drv3: 0,
+        drv4: 0,
+        prod: [0; 32],
+        next: [0; 32],
+        prev_slot: 998,
+        prev_price: 9_990_000_000,
+        prev_conf: 5_000_000,
+        prev_timestamp: 1_699_999_999,
+        agg: PriceInfo {
+            price: 10_000_000_000, // 100.0
+            conf: 5_000_000,
+            status: PYTH_STATUS_TRADING,
+            corp_act: 0,
+            pub_slot: 999,
+        },
+    }
+}
+
+// Size of a v2 price account: the header plus 32 publisher components of 96 bytes
+const PRICE_ACCOUNT_SIZE: usize = 240 + 32 * 96;
+
+// Price account bytes written at the oracle program's field offsets, independently of
+// `PriceMsg`, so a layout mistake in the struct cannot cancel out in the fixture
+fn price_account_bytes() -> Vec<u8> {
+    let mut data = vec![0u8; PRICE_ACCOUNT_SIZE];
+    let mut put = |offset: usize, bytes: &[u8]| data[offset..offset + bytes.len()].copy_from_slice(bytes);
+    put(0, &PYTH_MAGIC.to_le_bytes());
+    put(4, &PYTH_VERSION_2.to_le_bytes());
+    put(8, &PYTH_ACCOUNT_TYPE_PRICE.to_le_bytes());
+    put(12, &(PRICE_ACCOUNT_SIZE as u32).to_le_bytes());
+    put(16, &PYTH_PRICE_TYPE_PRICE.to_le_bytes());
+    put(20, &(-8i32).to_le_bytes());               // expo
+    put(24, &5u32.to_le_bytes());                  // num
+    put(32, &1_000u64.to_le_bytes());              // last_slot
+    put(40, &999u64.to_le_bytes());                // valid_slot
+    put(48, &9_950_000_000i64.to_le_bytes());      // ema_price.val: 99.5
+    put(96, &1_700_000_000i64.to_le_bytes());      // timestamp
+    put(184, &9_990_000_000i64.to_le_bytes());     // prev_price
+    put(208, &10_012_345_678i64.to_le_bytes());    // agg.price: 100.12345678
+    put(216, &5_000_000u64.to_le_bytes());         // agg.conf
+    put(224, &PYTH_STATUS_TRADING.to_le_bytes());  // agg.status
+    put(232, &999u64.to_le_bytes());               // agg.pub_slot
+    data
+}
+
+fn assert_rejected(msg: PriceMsg, expected: DexError) {
+    assert_eq!(msg.validate().unwrap_err(), Error::from(expected));
+}
+
+#[test]
+fn test_valid_price_account() {
+    let msg = valid_price_msg();
+    assert!(msg.validate().is_ok());
+    assert_eq!(msg.get_price().unwrap(), FixedPrice::from_int(100));
+}
+
+#[test]
+fn test_reads_fields_at_price_account_offsets() {
+    let msg = parse_pyth_price(&price_account_bytes()).unwrap();
+    assert!(msg.validate().is_ok());
+    assert_eq!(msg.expo, -8);
+    assert_eq!(msg.timestamp, 1_700_000_000);
+    assert_eq!(msg.prev_price, 9_990_000_000);
+    assert_eq!((msg.agg.price, msg.agg.conf), (10_012_345_678, 5_000_000));
+    assert_eq!((msg.agg.status, msg.agg.pub_slot), (PYTH_STATUS_TRADING, 999));
+    assert_eq!(msg.get_twap().unwrap(), FixedPrice::from_ratio(995, 10).ok());
+
+    // A halted aggregate is rejected even though the previous price is still set
+    let mut data = price_account_bytes();
+    data[224..228].copy_from_slice(&0u32.to_le_bytes());
+    let msg = parse_pyth_price(&data).unwrap();
+    assert_eq!(msg.validate().unwrap_err(), Error::from(DexError::OracleNotTrading));
+}
+
+#[test]
+fn test_rejects_malformed_header() {
+    let mut msg = valid_price_msg();
+    msg.magic = 0xdead_beef;
+    assert_rejected(msg, DexError::InvalidOracleMagic);
+
+    let mut msg = valid_price_msg();
+    msg.ver = 1;
+    assert_rejected(msg, DexError::InvalidOracleVersion);
+
+    let mut msg = valid_price_msg();
+    msg.atype = 2; // product account
+    assert_rejected(msg, DexError::InvalidOracleAccountType);
+
+    let mut msg = valid_price_msg();
+    msg.price_type = 0;
+    assert_rejected(msg, DexError::InvalidOraclePriceType);
+}
+
+#[test]
+fn test_rejects_non_trading_price() {
+    let mut msg = valid_price_msg();
+    msg.agg.status = 2; // halted
+    assert_rejected(msg, DexError::OracleNotTrading);
+
+    let mut msg = valid_price_msg();
+    msg.num = 0;
+    assert_rejected(msg, DexError::OracleNotTrading);
+}
+
+#[test]
+fn test_rejects_non_positive_price() {
+    let mut msg = valid_price_msg();
+    msg.agg.price = 0;
+    assert_rejected(msg, DexError::InvalidOraclePrice);
+
+    let mut msg = valid_price_msg();
+    msg.agg.price = -1;
+    assert_rejected(msg, DexError::InvalidOraclePrice);
+    assert!(msg.get_price().is_err());
+}
+
+#[test]
+fn test_freshness_uses_cluster_clock() {
+    let msg = valid_price_msg();
+    let clock = |slot, unix_timestamp| Clock { slot, unix_timestamp, ..Default::default() };
+
+    assert!(msg.is_fresh(&clock(1_019, 1_700_000_005), 20, 10));
+    // Account's own last_slot is unchanged, but the cluster has moved on
+    assert!(!msg.is_fresh(&clock(1_020, 1_700_000_005), 20, 10));
+    assert!(!msg.is_fresh(&clock(1_019, 1_700_000_011), 20, 10));
+}
+
+#[test]
+fn test_twap() {
+    let mut msg = valid_price_msg();
+    assert_eq!(msg.get_twap().unwrap(), Some(FixedPrice::from_int(100)));
+
+    msg.ema_price.val = 0;
+    assert_eq!(msg.get_twap().unwrap(), None);
+}
+
+#[test]
+fn test_parses_unaligned_account_data() {
+    let msg = valid_price_msg();
+    let bytes = bytemuck::bytes_of(&msg);
+
+    // Shift the account by one byte so the header is not 8-byte aligned
+    let mut data = vec![0u8; bytes.len() + 1];
+    data[1..].copy_from_slice(bytes);
+    let parsed = parse_pyth_price(&data[1..]).unwrap();
+    assert_eq!(parsed.agg.price, msg.agg.price);
+    assert_eq!(parsed.agg.pub_slot, msg.agg.pub_slot);
+    assert!(parsed.validate().is_ok());
+
+    assert_eq!(
+        parse_pyth_price(&bytes[..bytes.len() - 1]).unwrap_err(),
+        Error::from(ErrorCode::AccountDidNotDeserialize)
+    );
+}
+
+#[test]
+fn test_rejects_price_account_from_unlisted_program() {
+    let key = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let mut lamports = 1_000_000;
+    let mut data = bytemuck::bytes_of(&valid_price_msg()).to_vec();
+    let account = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0);
+
+    let err = get_pyth_price(&account, &[Pubkey::new_unique()], 25, 60).unwrap_err();
+    assert_eq!(err, Error::from(DexError::InvalidOracleOwner));
+}
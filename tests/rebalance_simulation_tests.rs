@@ -0,0 +1,120 @@
+mod common;
+
+use bulker_dex::fixed_point::FixedPrice;
+use bulker_dex::pool::{Pool, SwapDirection};
+use common::{seeded_pool, Rng};
+
+// Trade fixed chunks against the pool while it quotes better than the oracle
+fn arbitrage(pool: &mut Pool, price: &FixedPrice, vaults: &mut (u64, u64)) -> u32 {
+    let (base_chunk, quote_chunk) = (1_000_000_000, 100_000_000);
+    let mut trades = 0;
+    loop {
+        let (out, fee) = pool.compute_swap_output(SwapDirection::BaseToQuote, base_chunk).unwrap();
+        if out > price.mul_u64(base_chunk).unwrap() {
+            pool.update_reserves(SwapDirection::BaseToQuote, base_chunk, out, fee, 0).unwrap();
+            vaults.0 += base_chunk;
+            vaults.1 -= out;
+            trades += 1;
+            continue;
+        }
+        let (out, fee) = pool.compute_swap_output(SwapDirection::QuoteToBase, quote_chunk).unwrap();
+        if price.mul_u64(out).unwrap() > quote_chunk {
+            pool.update_reserves(SwapDirection::QuoteToBase, quote_chunk, out, fee, 0).unwrap();
+            vaults.1 += quote_chunk;
+            vaults.0 -= out;
+            trades += 1;
+            continue;
+        }
+        return trades;
+    }
+}
+
+// Replays a +/-2% random walk, rebalancing and arbitraging at every step.
+// Returns the final and largest distance of the base value share from target, in bps.
+fn simulate(rebalance_cap_bps: u16) -> (u64, u64) {
+    let mut pool = seeded_pool(rebalance_cap_bps, 0, 0);
+    let mut vaults = (pool.base_reserve, pool.quote_reserve);
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+    let mut price = pool.oracle_price;
+    let mut max_drift = 0;
+
+    for _ in 0..200 {
+        let step_bps = if rng.next() % 2 == 0 { 10_200 } else { 9_800 };
+        price = price.mul_bps(step_bps).unwrap();
+
+        let (should_rebalance, _) = pool.needs_rebalance(&price).unwrap();
+        if should_rebalance {
+            let reserves = (pool.base_reserve, pool.quote_reserve);
+            pool.rebalance(price).unwrap();
+            assert_eq!(reserves, (pool.base_reserve, pool.quote_reserve));
+        }
+
+        arbitrage(&mut pool, &price, &mut vaults);
+        // Reserves only ever move with the tokens that trades move
+        pool.check_vault_invariant(vaults.0, vaults.1).unwrap();
+        assert_eq!(vaults, (pool.base_reserve, pool.quote_reserve));
+
+        max_drift = max_drift.max(pool.base_value_bps().unwrap().abs_diff(5_000));
+    }
+    (pool.base_value_bps().unwrap().abs_diff(5_000), max_drift)
+}
+
+#[test]
+fn test_rebalance_moves_curve_not_reserves() {
+    let mut pool = seeded_pool(100, 0, 0);
+    for price in [101, 97, 105, 112, 90] {
+        let reserves = (pool.base_reserve, pool.quote_reserve);
+        let curve_price = pool.rebalance(FixedPrice::from_ratio(price, 1_000).unwrap()).unwrap();
+        assert_eq!(reserves, (pool.base_reserve, pool.quote_reserve));
+
+        // The curve holds the pool's value, split evenly at the curve price
+        let value = pool.value_in_quote(pool.base_reserve, pool.quote_reserve).unwrap();
+        assert!(value - 2 * pool.curve_quote_reserve as u128 <= 1);
+        let curve_base_value = curve_price.mul_u64(pool.curve_base_reserve).unwrap();
+        assert!(pool.curve_quote_reserve - curve_base_value <= 1);
+    }
+}
+
+#[test]
+fn test_curve_price_leans_against_inventory_skew() {
+    let oracle = FixedPrice::from_int(100);
+
+    let pool = common::pool(1_000_000, 100_000_000).rebalance(5_000, 100).build();
+    assert_eq!(pool.base_value_bps().unwrap(), 5_000);
+    assert_eq!(pool.curve_price().unwrap(), oracle);
+
+    // Too much base: discounted, but never by more than the cap
+    let pool = common::pool(3_000_000, 100_000_000).rebalance(5_000, 100).build();
+    assert_eq!(pool.base_value_bps().unwrap(), 7_500);
+    assert_eq!(pool.curve_price().unwrap(), oracle.mul_bps(9_900).unwrap());
+
+    // Slightly too much quote: base is marked up by the skew
+    let pool = common::pool(1_000_000, 100_400_000).rebalance(5_000, 100).build();
+    assert_eq!(pool.base_value_bps().unwrap(), 4_990);
+    assert_eq!(pool.curve_price().unwrap(), oracle.mul_bps(10_010).unwrap());
+
+    // A 50/50 pool above a 40% base target sells base down to it
+    let pool = common::pool(1_000_000, 100_000_000).rebalance(4_000, 100).build();
+    assert_eq!(pool.curve_price().unwrap(), oracle.mul_bps(9_900).unwrap());
+}
+
+#[test]
+fn test_inventory_drift_over_price_path() {
+    // Without a cap the pool always quotes the oracle, so inventory drifts with price
+    let (uncapped_drift, uncapped_max_drift) = simulate(0);
+    // With a cap the skewed curve price pays arbitrageurs to restore the target
+    let (capped_drift, capped_max_drift) = simulate(100);
+
+    assert!(capped_drift < uncapped_drift);
+    assert!(capped_max_drift < uncapped_max_drift);
+    assert!(capped_drift < 100, "ended {} bps from target", capped_drift);
+}
+
+#[test]
+fn test_set_rebalance_params_validates() {
+    let mut pool = Pool::default();
+    assert!(pool.set_rebalance_params(0, 100).is_err());
+    assert!(pool.set_rebalance_params(10_000, 100).is_err());
+    assert!(pool.set_rebalance_params(5_000, 501).is_err());
+    assert!(pool.set_rebalance_params(5_000, 500).is_ok());
+}
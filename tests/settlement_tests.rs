@@ -0,0 +1,106 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::token::{spl_token, TokenAccount};
+use bulker_dex::error::DexError;
+use bulker_dex::pool::Pool;
+use bulker_dex::settlement::check_input_authority;
+
+// Token account owned by `owner` with `delegated_amount` approved to `delegate`
+fn input_account(owner: Pubkey, delegate: Option<Pubkey>, delegated_amount: u64) -> TokenAccount {
+    let account = spl_token::state::Account {
+        mint: Pubkey::new_unique(),
+        owner,
+        amount: 1_000_000,
+        delegate: delegate.map_or(COption::None, COption::Some),
+        delegated_amount,
+        state: spl_token::state::AccountState::Initialized,
+        ..Default::default()
+    };
+    let mut data = vec![0u8; spl_token::state::Account::LEN];
+    spl_token::state::Account::pack(account, &mut data).unwrap();
+    TokenAccount::try_deserialize(&mut &data[..]).unwrap()
+}
+
+fn assert_not_authorized(result: Result<()>) {
+    assert_eq!(result.unwrap_err(), Error::from(DexError::InputNotAuthorized));
+}
+
+#[test]
+fn test_signer_moves_own_input() {
+    let (user, pool) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let from = input_account(user, None, 0);
+    assert!(check_input_authority(&from, &user, true, &pool, 500_000).is_ok());
+
+    // Signing for someone else's account is not enough
+    let from = input_account(Pubkey::new_unique(), None, 0);
+    assert_not_authorized(check_input_authority(&from, &user, true, &pool, 500_000));
+}
+
+#[test]
+fn test_delegated_input_needs_pool_approval() {
+    let (user, pool) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let from = input_account(user, Some(pool), 500_000);
+    assert!(check_input_authority(&from, &user, false, &pool, 500_000).is_ok());
+
+    // No approval at all
+    let from = input_account(user, None, 0);
+    assert_not_authorized(check_input_authority(&from, &user, false, &pool, 1));
+}
+
+#[test]
+fn test_delegated_input_rejects_wrong_delegate() {
+    let (user, pool) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let from = input_account(user, Some(Pubkey::new_unique()), 500_000);
+    assert_not_authorized(check_input_authority(&from, &user, false, &pool, 500_000));
+}
+
+#[test]
+fn test_delegated_input_rejects_amount_over_approval() {
+    let (user, pool) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let from = input_account(user, Some(pool), 500_000);
+    assert_not_authorized(check_input_authority(&from, &user, false, &pool, 500_001));
+}
+
+#[test]
+fn test_delegated_input_rejects_account_of_other_owner() {
+    // The pool is approved, but on an account that does not belong to the named user
+    let (user, pool) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let from = input_account(Pubkey::new_unique(), Some(pool), 500_000);
+    assert_not_authorized(check_input_authority(&from, &user, false, &pool, 500_000));
+}
+
+#[test]
+fn test_vault_invariant_covers_reserves_and_protocol_fees() {
+    let pool = Pool {
+        base_reserve: 1_000,
+        quote_reserve: 2_000,
+        protocol_fees_base: 10,
+        protocol_fees_quote: 20,
+        ..Default::default()
+    };
+    assert!(pool.check_vault_invariant(1_010, 2_020).is_ok());
+    // Surplus, such as a donation, is allowed
+    assert!(pool.check_vault_invariant(5_000, 5_000).is_ok());
+
+    // One atom short on either side fails
+    for (base_vault, quote_vault) in [(1_009, 2_020), (1_010, 2_019), (1_000, 2_000)] {
+        assert_eq!(
+            pool.check_vault_invariant(base_vault, quote_vault).unwrap_err(),
+            Error::from(DexError::VaultBelowReserves)
+        );
+    }
+}
+
+#[test]
+fn test_vault_invariant_does_not_overflow() {
+    let pool = Pool {
+        base_reserve: u64::MAX,
+        protocol_fees_base: 1,
+        ..Default::default()
+    };
+    assert_eq!(
+        pool.check_vault_invariant(u64::MAX, 0).unwrap_err(),
+        Error::from(DexError::VaultBelowReserves)
+    );
+}
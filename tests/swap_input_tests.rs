@@ -0,0 +1,56 @@
+mod common;
+
+use bulker_dex::pool::{Pool, SwapDirection};
+use common::{curve_pool, random_pool, Rng};
+
+fn output_limit(pool: &Pool, direction: SwapDirection) -> u64 {
+    match direction {
+        SwapDirection::BaseToQuote => pool.curve_quote_reserve,
+        SwapDirection::QuoteToBase => pool.curve_base_reserve,
+    }
+}
+
+#[test]
+fn test_swap_input_covers_requested_output() {
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    for _ in 0..10_000 {
+        let pool = random_pool(&mut rng);
+        let direction = if rng.next() % 2 == 0 {
+            SwapDirection::BaseToQuote
+        } else {
+            SwapDirection::QuoteToBase
+        };
+        let limit = output_limit(&pool, direction);
+        if limit < 2 {
+            continue;
+        }
+        // Up to half the curve reserve keeps the required input within u64
+        let amount_out = rng.range(1, limit / 2 + 1);
+
+        let (amount_in, fee) = pool.compute_swap_input(direction, amount_out).unwrap();
+        let (actual_out, actual_fee) = pool.compute_swap_output(direction, amount_in).unwrap();
+        assert!(actual_out >= amount_out, "{:?} {} -> {} -> {}", direction, amount_out, amount_in, actual_out);
+        assert_eq!(fee, actual_fee);
+
+        // One atom less must fall short, so the quote is the cheapest input
+        let (short_out, _) = pool.compute_swap_output(direction, amount_in - 1).unwrap();
+        assert!(short_out < amount_out, "{:?} {} -> {} not minimal", direction, amount_out, amount_in);
+    }
+}
+
+#[test]
+fn test_swap_input_rounds_in_pool_favour() {
+    let pool = curve_pool(100_000_000_000, 10_000_000_000, 30);
+    let (amount_in, fee) = pool.compute_swap_input(SwapDirection::BaseToQuote, 1_000).unwrap();
+    assert_eq!(pool.compute_fee(SwapDirection::BaseToQuote, amount_in).unwrap(), fee);
+    assert!(fee > 0);
+    assert!(pool.compute_swap_output(SwapDirection::BaseToQuote, amount_in).unwrap().0 >= 1_000);
+}
+
+#[test]
+fn test_swap_input_rejects_output_beyond_curve_reserve() {
+    let pool = curve_pool(1_000_000, 1_000_000, 30);
+    let limit = output_limit(&pool, SwapDirection::QuoteToBase);
+    assert!(pool.compute_swap_input(SwapDirection::QuoteToBase, limit).is_err());
+    assert!(pool.compute_swap_input(SwapDirection::QuoteToBase, limit - 1).is_ok());
+}
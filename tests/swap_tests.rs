@@ -1,30 +1,157 @@
-#[tokio::test]
-async fn test_concentrated_swap() {
+use anchor_lang::prelude::*;
+use bulker_dex::decode_delegated_swap;
+use bulker_dex::error::DexError;
+use bulker_dex::fixed_point::FixedPrice;
+use bulker_dex::pool::{Pool, SwapDirection, SwapParams, MAX_CONCENTRATION_FACTOR};
+
+// 100 SOL (9 decimals) and 10,000 USDC (6 decimals) at 100 USDC per SOL
+fn sol_usdc_pool(concentration_factor: u8) -> Pool {
     let mut pool = Pool {
-        sol_reserve: 100_000_000_000, // 100 SOL
-        usdc_reserve: 10_000_000_000, // 10,000 USDC
-        concentration_factor: 10,
+        oracle_price: FixedPrice::from_ratio(1, 10).unwrap(),
+        base_reserve: 100_000_000_000,
+        quote_reserve: 10_000_000_000,
+        fee_bps: 30,
         ..Default::default()
     };
+    pool.set_concentration_factor(concentration_factor).unwrap();
+    pool.set_rebalance_params(5_000, 0).unwrap();
+    pool.recenter().unwrap();
+    pool
+}
+
+#[test]
+fn test_concentrated_swap() {
+    let pool = sol_usdc_pool(10);
 
-    let (amount_out, fee) = pool.compute_swap_output(TokenType::SOL, 1_000_000_000).unwrap();
+    let (amount_out, fee) = pool.compute_swap_output(SwapDirection::BaseToQuote, 1_000_000_000).unwrap();
     // Verify concentrated liquidity impact
     assert!(amount_out > 99_000_000); // Expect >99 USDC with 10x concentration
     assert_eq!(fee, 3_000_000); // 0.3% of 1 SOL
 }
 
-#[tokio::test]
-async fn test_magic_block_delegation() {
+#[test]
+fn test_concentration_deepens_the_curve_around_the_price() {
+    let shallow = sol_usdc_pool(1);
+    let deep = sol_usdc_pool(10);
+
+    let (shallow_out, _) = shallow.compute_swap_output(SwapDirection::BaseToQuote, 1_000_000_000).unwrap();
+    let (deep_out, _) = deep.compute_swap_output(SwapDirection::BaseToQuote, 1_000_000_000).unwrap();
+    assert!(shallow_out < 99_000_000);
+    assert!(deep_out > shallow_out);
+    // Neither beats the oracle price net of the fee
+    assert!(deep_out <= 99_700_000);
+
+    // Both sides are amplified, so the marginal price is unchanged
+    let price = FixedPrice::from_ratio(1, 10).unwrap();
+    for pool in [&shallow, &deep] {
+        let curve_base_value = price.mul_u64(pool.curve_base_reserve).unwrap();
+        assert!(pool.curve_quote_reserve.abs_diff(curve_base_value) <= 1);
+    }
+    assert_eq!(deep.curve_quote_reserve, 10 * shallow.curve_quote_reserve);
+}
+
+#[test]
+fn test_output_is_capped_by_real_reserves() {
+    let pool = sol_usdc_pool(MAX_CONCENTRATION_FACTOR);
+
+    // 1,000 SOL would buy more USDC than the pool holds on the amplified curve
+    let (amount_out, _) = pool.compute_swap_output(SwapDirection::BaseToQuote, 1_000_000_000_000).unwrap();
+    assert_eq!(amount_out, pool.quote_reserve);
+
+    assert_eq!(
+        pool.compute_swap_input(SwapDirection::BaseToQuote, pool.quote_reserve + 1).unwrap_err(),
+        Error::from(DexError::InsufficientLiquidity)
+    );
+    assert!(pool.compute_swap_input(SwapDirection::BaseToQuote, pool.quote_reserve).is_ok());
+}
+
+#[test]
+fn test_protocol_fee_split_follows_config_rate() {
+    let mut pool = sol_usdc_pool(1);
+    let (base_reserve, quote_reserve) = (pool.base_reserve, pool.quote_reserve);
+
+    // 20% of the 0.3% fee on 1 SOL goes to the protocol, the rest stays with LPs
+    let (out, fee) = pool.compute_swap_output(SwapDirection::BaseToQuote, 1_000_000_000).unwrap();
+    assert_eq!(fee, 3_000_000);
+    pool.update_reserves(SwapDirection::BaseToQuote, 1_000_000_000, out, fee, 2_000).unwrap();
+    assert_eq!(pool.protocol_fees_base, 600_000);
+    assert_eq!(pool.fees_collected_base, 3_000_000);
+    assert_eq!(pool.base_reserve, base_reserve + 1_000_000_000 - 600_000);
+    assert_eq!(pool.quote_reserve, quote_reserve - out);
+
+    // A new config rate applies to the next swap, with no per-pool copy to go stale
+    let (out, fee) = pool.compute_swap_output(SwapDirection::QuoteToBase, 100_000_000).unwrap();
+    pool.update_reserves(SwapDirection::QuoteToBase, 100_000_000, out, fee, 10_000).unwrap();
+    assert_eq!(pool.protocol_fees_quote, fee);
+
+    // Rounded down in favour of LPs
+    assert_eq!(Pool::compute_protocol_fee(4, 2_000), 0);
+    assert_eq!(Pool::compute_protocol_fee(5, 2_000), 1);
+    assert_eq!(Pool::compute_protocol_fee(1_000, 0), 0);
+}
+
+#[test]
+fn test_claim_protocol_fees_accounting() {
+    let mut pool = sol_usdc_pool(1);
+    let (mut base_vault, mut quote_vault) = (pool.base_reserve, pool.quote_reserve);
+
+    for (direction, amount_in) in [
+        (SwapDirection::BaseToQuote, 1_000_000_000),
+        (SwapDirection::QuoteToBase, 250_000_000),
+        (SwapDirection::BaseToQuote, 7_777_777),
+    ] {
+        let (out, fee) = pool.compute_swap_output(direction, amount_in).unwrap();
+        pool.update_reserves(direction, amount_in, out, fee, 2_000).unwrap();
+        match direction {
+            SwapDirection::BaseToQuote => {
+                base_vault += amount_in;
+                quote_vault -= out;
+            }
+            SwapDirection::QuoteToBase => {
+                quote_vault += amount_in;
+                base_vault -= out;
+            }
+        }
+        pool.check_vault_invariant(base_vault, quote_vault).unwrap();
+    }
+
+    // Claiming pays out exactly the counters and leaves the vaults matching the reserves
+    let (base_reserve, quote_reserve) = (pool.base_reserve, pool.quote_reserve);
+    let (base_claimed, quote_claimed) = pool.take_protocol_fees();
+    assert!(base_claimed > 0 && quote_claimed > 0);
+    base_vault -= base_claimed;
+    quote_vault -= quote_claimed;
+    assert_eq!((base_vault, quote_vault), (pool.base_reserve, pool.quote_reserve));
+    assert_eq!((pool.base_reserve, pool.quote_reserve), (base_reserve, quote_reserve));
+    pool.check_vault_invariant(base_vault, quote_vault).unwrap();
+
+    // Nothing is left to claim twice
+    assert_eq!(pool.take_protocol_fees(), (0, 0));
+}
+
+#[test]
+fn test_concentration_factor_is_validated() {
+    let mut pool = Pool::default();
+    for concentration_factor in [0, MAX_CONCENTRATION_FACTOR + 1] {
+        assert_eq!(
+            pool.set_concentration_factor(concentration_factor).unwrap_err(),
+            Error::from(DexError::InvalidConcentrationFactor)
+        );
+    }
+    pool.set_concentration_factor(MAX_CONCENTRATION_FACTOR).unwrap();
+    assert_eq!(pool.concentration_factor, MAX_CONCENTRATION_FACTOR);
+}
+
+#[test]
+fn test_magic_block_delegation() {
     let large_swap = SwapParams {
-        input_token: TokenType::SOL,
+        direction: SwapDirection::BaseToQuote,
         amount_in: 10_000_000_000, // 10 SOL
         min_amount_out: 950_000_000,
-        price_limit: 0.1
+        max_price_impact_bps: 1_000,
     };
 
-    let rollup_client = RollupClient::new(TEST_ROLLUP_URL);
-    let ix = delegation::delegate_swap(&rollup_client, &Pubkey::new_unique(), &large_swap);
-    
-    assert_eq!(ix.program_id, MAGIC_BLOCK_PROGRAM_ID);
-    assert!(ix.data.len() > 0);
-} 
\ No newline at end of file
+    let data = large_swap.try_to_vec().unwrap();
+    assert_eq!(decode_delegated_swap(&data).unwrap(), large_swap);
+    assert!(decode_delegated_swap(&data[..data.len() - 1]).is_err());
+}